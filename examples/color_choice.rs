@@ -5,9 +5,7 @@ extern crate rand;
 use vrcounter::*;
 use vrcounter::color::*;
 use std::sync::Arc;
-use std::cell::RefCell;
-use std::rc::Rc;
-use cage::{Frame};
+use cage::{Frame, Offset};
 
 #[derive(Clone, Debug)]
 enum Msg {
@@ -17,63 +15,51 @@ enum Msg {
 #[derive(Clone, Debug)]
 struct Out;
 
-#[derive(Clone, Debug)]
-struct App;
-
 #[derive(Clone)]
-struct Model {
-    wailing: Rc<RefCell<Box<Wailing<TouchMsg>>>>,
+struct App {
+    wail_star: WailStar<TouchMsg>,
 }
 
 impl Star for App {
-    type Mdl = Model;
+    type Mdl = WailStarModel<TouchMsg>;
     type Msg = Msg;
     type Out = Out;
 
-    fn init(&self) -> Model {
-        let frame = Frame::from((0.20, 0.20, 0.20));
-        let wail = color_wail(CYAN, frame).add_touch()
-                                          .place_before(color_wail(RED, Frame::default()));
-        let wailing = wail.summon();
-        Model {
-            wailing: Rc::new(RefCell::new(wailing))
-        }
+    fn init(&self) -> WailStarModel<TouchMsg> {
+        self.wail_star.init()
     }
 
-    fn update(&self, model: &Model, msg: &Msg) -> Model {
+    fn update(&self, model: &WailStarModel<TouchMsg>, msg: &Msg) -> WailStarModel<TouchMsg> {
         match msg {
             &Msg::SendToWailing(wailing_in) => {
-                {
-                    let mut wailing = model.wailing.as_ref().borrow_mut();
-                    let out = wailing.update(&wailing_in);
-                    match out {
-                        TouchMsg::TouchMove => {
-                            println!("Touch move!");
-                        }
-                        _ => ()
-                    }
+                let new_model = self.wail_star.update(model, &wailing_in);
+                let mut well = Well::new(|out| match out {
+                    TouchMsg::TouchMove => Some(()),
+                    _ => None,
+                });
+                self.wail_star.report(&new_model, &mut well);
+                for _ in well.messages {
+                    println!("Touch move!");
                 }
-                model.clone()
+                new_model
             }
         }
     }
 
-    fn view(&self, model: &Model) -> Vision<Msg> {
+    fn view(&self, model: &WailStarModel<TouchMsg>) -> Vision<Msg> {
         let mut vision = Vision::new();
-        let wailing_mut = model.wailing.as_ref().borrow_mut();
-        let wail_vision = wailing_mut.view();
-        vision.add_vision(wail_vision, |wailing_msg| {
-            match wailing_msg {
-                WailingIn::Hand(hand) => Some(Msg::SendToWailing(WailingIn::Hand(hand))),
-                _ => None
-            }
-        });
+        vision.add_vision(self.wail_star.view(model), |wailing_in| Some(Msg::SendToWailing(wailing_in)));
         vision
     }
 }
 
 
 fn main() {
-    let star_builder = Arc::new(move || App);
+    let star_builder = Arc::new(move || {
+        let frame = Frame::from((0.20, 0.20, 0.20));
+        let wail = color_wail(CYAN, frame).add_touch()
+                                          .place_before(color_wail(RED, Frame::default()));
+        App { wail_star: wail.to_star(Offset::default()) }
+    });
     vrcounter::start(star_builder)
 }
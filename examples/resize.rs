@@ -0,0 +1,84 @@
+extern crate vrcounter;
+extern crate cage;
+
+use vrcounter::*;
+use vrcounter::color::*;
+use std::sync::Arc;
+use cage::{Cage, Frame, Offset};
+
+const MIN_SIDE: f32 = 0.05;
+
+#[derive(Clone, Debug)]
+enum Msg {
+    Hand(Hand),
+}
+
+#[derive(Clone, Debug)]
+struct Model {
+    cage: Cage,
+    stretch: Stretch,
+    turned: f32,
+}
+
+// Width and height change about the center; depth stays.
+fn scaled(cage: &Cage, scale: f32) -> Cage {
+    let (left, right, bottom, top, far, near) = cage.limits();
+    let frame = Frame::from((((right - left) * scale).max(MIN_SIDE), ((top - bottom) * scale).max(MIN_SIDE), near - far));
+    Cage::from((frame, Offset::from(((left + right) / 2.0, (bottom + top) / 2.0, (far + near) / 2.0))))
+}
+
+// A panel that grows and shrinks as two hands grabbing it move apart or together.
+#[derive(Clone)]
+struct Panel {
+    cage: Cage,
+    patch_id: u64,
+    mist_id: u64,
+}
+
+impl Star for Panel {
+    type Mdl = Model;
+    type Msg = Msg;
+    type Out = ();
+
+    fn init(&self) -> Model {
+        Model { cage: self.cage, stretch: Stretch::default(), turned: 0.0 }
+    }
+
+    fn view(&self, model: &Model) -> Vision<Msg> {
+        let mut vision = Vision::new();
+        let color = if model.stretch.is_stretching() { CYAN } else { BLUE };
+        vision.add_patch(Patch::from_cage(&model.cage, color, Sigil::Fill, self.patch_id));
+        vision.add_mist(Mist::new(self.mist_id, model.cage), |wish| {
+            match wish {
+                Wish::SenseHand(hand) => Some(Msg::Hand(hand)),
+                _ => None,
+            }
+        });
+        vision
+    }
+
+    fn update(&self, model: &Model, message: &Msg) -> Model {
+        match message {
+            &Msg::Hand(hand) => {
+                let (stretch, stretch_msg) = model.stretch.sense(&model.cage, &hand);
+                match stretch_msg {
+                    StretchMsg::Changed { scale, rotation } => {
+                        Model { cage: scaled(&model.cage, scale), stretch: stretch, turned: model.turned + rotation }
+                    },
+                    StretchMsg::Ended => {
+                        println!("Turned {:.0} degrees", model.turned.to_degrees());
+                        Model { stretch: stretch, turned: 0.0, ..model.clone() }
+                    },
+                    StretchMsg::None => Model { stretch: stretch, ..model.clone() },
+                }
+            },
+        }
+    }
+}
+
+fn main() {
+    let star_builder = Arc::new(move || {
+        Panel { cage: Cage::from((-0.2, 0.2, -0.15, 0.15, -0.05, 0.05)), patch_id: 1, mist_id: 2 }
+    });
+    vrcounter::start(star_builder)
+}
@@ -0,0 +1,69 @@
+extern crate vrcounter;
+extern crate cage;
+
+use vrcounter::*;
+use vrcounter::text_field::*;
+use std::sync::Arc;
+use cage::{Cage, Offset};
+
+#[derive(Clone, Debug)]
+enum Msg {
+    Field(TextFieldMsg),
+    Keyboard(WailingIn),
+}
+
+#[derive(Clone)]
+struct App {
+    field: TextField,
+    keyboard: WailStar<Option<TextEdit>>,
+}
+
+#[derive(Clone)]
+struct Model {
+    field: TextFieldModel,
+    keyboard: WailStarModel<Option<TextEdit>>,
+}
+
+impl Star for App {
+    type Mdl = Model;
+    type Msg = Msg;
+    type Out = ();
+
+    fn init(&self) -> Model {
+        Model { field: self.field.init(), keyboard: self.keyboard.init() }
+    }
+
+    fn update(&self, model: &Model, msg: &Msg) -> Model {
+        match msg {
+            &Msg::Field(ref field_msg) => {
+                Model { field: self.field.update(&model.field, field_msg), ..model.clone() }
+            },
+            &Msg::Keyboard(wailing_in) => {
+                let keyboard = self.keyboard.update(&model.keyboard, &wailing_in);
+                let mut well = Well::new(|text_edit| text_edit);
+                self.keyboard.report(&keyboard, &mut well);
+                let field = well.messages.into_iter().fold(model.field.clone(), |field, text_edit| {
+                    self.field.update(&field, &TextFieldMsg::Edit(text_edit))
+                });
+                Model { field: field, keyboard: keyboard }
+            },
+        }
+    }
+
+    fn view(&self, model: &Model) -> Vision<Msg> {
+        let mut vision = Vision::new();
+        vision.add_vision(self.field.view(&model.field), |field_msg| Some(Msg::Field(field_msg)));
+        vision.add_vision(self.keyboard.view(&model.keyboard), |wailing_in| Some(Msg::Keyboard(wailing_in)));
+        vision
+    }
+}
+
+fn main() {
+    let star_builder = Arc::new(move || {
+        App {
+            field: text_area(Cage::from((-0.35, 0.35, 0.05, 0.35, -0.02, 0.0)), "Hello"),
+            keyboard: keyboard_wail().to_star(Offset::from((0.0, -0.2, 0.0))),
+        }
+    });
+    vrcounter::start(star_builder)
+}
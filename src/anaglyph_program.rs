@@ -0,0 +1,79 @@
+extern crate glium;
+
+use glium::{Display, Program, VertexBuffer, Surface};
+use glium::index::{NoIndices, PrimitiveType};
+use glium::texture::Texture2d;
+
+// Combines two eye textures into one red/cyan image: red from the left eye, green and
+// blue from the right.
+pub struct AnaglyphProgram {
+    program: glium::Program,
+    vertex_buffer: VertexBuffer<Vertex>,
+    indices: glium::index::NoIndices,
+}
+
+impl AnaglyphProgram {
+    pub fn new(display: &Display) -> Self {
+        let sw = Vertex { position: [-1.0, -1.0], tex_coords: [0.0, 0.0] };
+        let se = Vertex { position: [1.0, -1.0], tex_coords: [1.0, 0.0] };
+        let nw = Vertex { position: [-1.0, 1.0], tex_coords: [0.0, 1.0] };
+        let ne = Vertex { position: [1.0, 1.0], tex_coords: [1.0, 1.0] };
+        let vertices = vec![sw, se, nw, ne];
+        AnaglyphProgram {
+            program: Program::from_source(display, VERTEX_SHADER, FRAGMENT_SHADER, None).unwrap(),
+            vertex_buffer: VertexBuffer::new(display, &vertices).unwrap(),
+            indices: NoIndices(PrimitiveType::TriangleStrip),
+        }
+    }
+
+    pub fn draw<T: Surface>(&self, surface: &mut T, left: &Texture2d, right: &Texture2d) {
+        let uniforms = uniform! {
+            left_eye: left.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Linear),
+            right_eye: right.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Linear),
+        };
+        surface.draw(
+            &self.vertex_buffer,
+            &self.indices,
+            &self.program,
+            &uniforms,
+            &Default::default()
+        ).unwrap();
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Vertex {
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+}
+implement_vertex!(Vertex, position, tex_coords);
+
+static VERTEX_SHADER: &'static str = r#"
+        #version 140
+
+        in vec2 position;
+        in vec2 tex_coords;
+
+        out vec2 v_tex_coords;
+
+        void main() {
+            v_tex_coords = tex_coords;
+            gl_Position = vec4(position, 0.0, 1.0);
+        }
+    "#;
+
+static FRAGMENT_SHADER: &'static str = r#"
+        #version 140
+
+        in vec2 v_tex_coords;
+        out vec4 color;
+
+        uniform sampler2D left_eye;
+        uniform sampler2D right_eye;
+
+        void main() {
+            vec3 left = texture(left_eye, v_tex_coords).rgb;
+            vec3 right = texture(right_eye, v_tex_coords).rgb;
+            color = vec4(left.r, right.g, right.b, 1.0);
+        }
+    "#;
@@ -9,28 +9,142 @@ use summoner::Summoner;
 use demon::Sight;
 use std::boxed::Box;
 use hand::Hand;
-use common::Wish;
+use common::{Wish, KeyPress, KeyCode};
+use mist::Mist;
+use mist_program::{mist_state, MistState};
+use gaze::{self, Ray, GazeDwell, Reticle};
+use std::time::Instant;
 use std::sync::Arc;
 use star::Star;
+use theme::{self, Theme};
+use light::Lighting;
 
 pub enum Message {
     Stop,
     EmitAnimationFrame,
     SetHand(Hand),
+    SetTheme(Theme),
+    KeyPress(KeyPress),
+    ReceiveChar(char),
+    SetGaze(Ray),
 }
 
 struct Model {
     summoner: Summoner,
     id_source: IdSource,
+    focus: Option<u64>,
+    gaze: GazeDwell,
+    reticle: Option<Reticle>,
 }
 
 fn init() -> Model {
     Model {
         summoner: Summoner::new(),
         id_source: IdSource::new(),
+        focus: None,
+        gaze: GazeDwell::default(),
+        reticle: None,
     }
 }
 
+fn focusable_ids(summoner: &Summoner) -> Vec<u64> {
+    focus_order(focusable_mists(summoner))
+}
+
+// Reading order: top to bottom by each mist's top edge, then left to right.
+pub fn focus_order(mut mists: Vec<Mist>) -> Vec<u64> {
+    use std::cmp::Ordering;
+    mists.sort_by(|a, b| {
+        let (a_left, _, _, a_top, _, _) = a.cage().limits();
+        let (b_left, _, _, b_top, _, _) = b.cage().limits();
+        match b_top.partial_cmp(&a_top).unwrap_or(Ordering::Equal) {
+            Ordering::Equal => match a_left.partial_cmp(&b_left).unwrap_or(Ordering::Equal) {
+                Ordering::Equal => a.id().cmp(&b.id()),
+                ordering => ordering,
+            },
+            ordering => ordering,
+        }
+    });
+    mists.iter().map(|mist| mist.id()).collect()
+}
+
+fn all_mists(summoner: &Summoner) -> Vec<Mist> {
+    let mut mists = Vec::new();
+    for demon_box in summoner.get_demon_boxes() {
+        mists.extend(demon_box.see().mists().values().cloned());
+    }
+    mists
+}
+
+fn focusable_mists(summoner: &Summoner) -> Vec<Mist> {
+    all_mists(summoner).into_iter().filter(|mist| mist.is_focusable()).collect()
+}
+
+fn gaze(mut model: Model, ray: Ray, now: Instant) -> Model {
+    let mists = all_mists(&model.summoner);
+    let hit = gaze::nearest_hit(&ray, mists.iter());
+    let dwell = hit.map(|(mist, _)| mist.dwell()).unwrap_or(Mist::default().dwell());
+    let (gaze, selected) = model.gaze.sense(hit.map(|(mist, _)| mist.id()), dwell, now);
+    if let Some(mist_id) = selected {
+        model.summoner.update_mist(mist_id, Wish::GazeSelect);
+        if focusable_ids(&model.summoner).contains(&mist_id) {
+            set_focus(&mut model, Some(mist_id));
+        }
+    }
+    model.reticle = Some(Reticle::on_ray(&ray, hit.map(|(_, distance)| distance), gaze.progress(dwell, now)));
+    model.gaze = gaze;
+    model
+}
+
+// Tab moves through focusable in order, wrapping at either end.
+pub fn next_focus(focusable: &Vec<u64>, current: Option<u64>, backwards: bool) -> Option<u64> {
+    if focusable.is_empty() {
+        return None;
+    }
+    let last = focusable.len() - 1;
+    let position = current.and_then(|id| focusable.iter().position(|&focusable_id| focusable_id == id));
+    let next_position = match (position, backwards) {
+        (None, false) => 0,
+        (None, true) => last,
+        (Some(i), false) => if i == last { 0 } else { i + 1 },
+        (Some(i), true) => if i == 0 { last } else { i - 1 },
+    };
+    Some(focusable[next_position])
+}
+
+fn set_focus(model: &mut Model, focus: Option<u64>) {
+    if focus == model.focus {
+        return;
+    }
+    if let Some(old_focus) = model.focus {
+        model.summoner.update_mist(old_focus, Wish::Focus(false));
+    }
+    model.focus = focus;
+    if let Some(new_focus) = focus {
+        model.summoner.update_mist(new_focus, Wish::Focus(true));
+    }
+}
+
+// Focus on a mist that has since left the scene is dropped.
+fn live_focus(model: &Model) -> Option<u64> {
+    model.focus.and_then(|id| if focusable_ids(&model.summoner).contains(&id) { Some(id) } else { None })
+}
+
+fn press_key(mut model: Model, key_press: KeyPress) -> Model {
+    model.focus = live_focus(&model);
+    match key_press.code {
+        KeyCode::Tab => {
+            let focus = next_focus(&focusable_ids(&model.summoner), model.focus, key_press.shift);
+            set_focus(&mut model, focus);
+        },
+        KeyCode::Escape => set_focus(&mut model, None),
+        _ => if let Some(focus) = model.focus {
+            model.summoner.update_mist(focus, Wish::KeyPress(key_press));
+        },
+    }
+    model
+}
+
 fn update(message: Message, mut model: Model) -> Option<Model> {
     match message {
         Message::Stop => None,
@@ -38,30 +152,69 @@ fn update(message: Message, mut model: Model) -> Option<Model> {
             (&mut model.summoner).update(Wish::Tick);
             Some(model)
         },
+        Message::SetTheme(next_theme) => {
+            theme::set_current(next_theme);
+            Some(model)
+        },
         Message::SetHand(hand) => {
-            // TODO Maybe check if hand occupies mist
             (&mut model.summoner).update(Wish::SenseHand(hand));
+            let pushed = focusable_mists(&model.summoner).into_iter()
+                                                         .find(|mist| mist_state(mist.cage(), &hand) == MistState::Pressed);
+            if let Some(mist) = pushed {
+                set_focus(&mut model, Some(mist.id()));
+            }
+            Some(model)
+        },
+        Message::KeyPress(key_press) => Some(press_key(model, key_press)),
+        Message::SetGaze(ray) => Some(gaze(model, ray, Instant::now())),
+        Message::ReceiveChar(c) => {
+            model.focus = live_focus(&model);
+            if let Some(focus) = model.focus {
+                model.summoner.update_mist(focus, Wish::ReceiveChar(c));
+            }
             Some(model)
+        },
+    }
+}
+
+// Wishes stars report outward, like haptic pulses, wait in the viewer for the user thread.
+fn pass_wishes(model: &mut Model, viewer: &Viewer) {
+    for wish in model.summoner.take_wishes() {
+        if let Wish::HapticPulse(pulse) = wish {
+            viewer.add_haptic(pulse);
         }
     }
 }
 
+// One turn of the app loop: update, pass wishes outward, and show the result.
+fn step(message: Message, model: Model, viewer: &Viewer) -> Option<Model> {
+    update(message, model).map(|mut next_model| {
+        pass_wishes(&mut next_model, viewer);
+        view(&next_model, viewer);
+        next_model
+    })
+}
+
+// The scene takes the lighting of the earliest summoned star that sets one, or the default.
 fn view(model: &Model, viewer: &Viewer) {
-    use patch::Patch;
-    use mist::Mist;
-    viewer.clear();
-    let demon_boxes = model.summoner.get_demon_boxes();
-    for demon_box in demon_boxes {
-        let vision_box: Box<Sight> = (&demon_box).see();
-        let patches: &HashMap<u64, Patch> = (*vision_box).patches();
-        for (_, patch) in patches.iter() {
-            viewer.add_patch(*patch);
-        }
-        let mists: &HashMap<u64, Mist> = (*vision_box).mists();
-        for (_, mist) in mists.iter() {
-            viewer.add_mist(*mist);
+    let mut patches = HashMap::new();
+    let mut mists = HashMap::new();
+    let mut lighting = None;
+    let mut debug_draws = Vec::new();
+    let mut demon_ids: Vec<&u64> = model.summoner.demons.keys().collect();
+    demon_ids.sort();
+    for demon_id in demon_ids {
+        let vision_box: Box<Sight> = (&model.summoner.demons[demon_id]).see();
+        patches.extend((*vision_box).patches().iter().map(|(&id, &patch)| (id, patch)));
+        mists.extend((*vision_box).mists().iter().map(|(&id, &mist)| (id, mist)));
+        if lighting.is_none() {
+            lighting = (*vision_box).lighting().cloned();
         }
+        debug_draws.extend((*vision_box).debug_draws().iter().cloned());
     }
+    viewer.set_scene(patches, mists, lighting.unwrap_or(Lighting::default()), debug_draws);
+    viewer.set_focus(live_focus(model));
+    viewer.set_reticle(model.reticle);
 }
 
 pub fn start<S: Star, F>(viewer: Viewer, star_builder: Arc<F>)
@@ -77,12 +230,9 @@ pub fn start<S: Star, F>(viewer: Viewer, star_builder: Arc<F>)
         loop {
             match rx.recv() {
                 Ok(message) => {
-                    match update(message, model) {
+                    match step(message, model, &viewer) {
                         Option::None => { break; },
-                        Option::Some(next_model) => {
-                            model = next_model;
-                            view(&model, &viewer);
-                        }
+                        Option::Some(next_model) => model = next_model,
                     }
                 }
                 Err(err) => {
@@ -99,3 +249,173 @@ pub fn stop(agent: Sender<Message>) {
     agent.send(Message::Stop).unwrap_or(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wail::{button_wail, ButtonMsg, WailStar, WailStarModel, WailingIn};
+    use vision::Vision;
+    use patch::{Patch, Sigil};
+    use cage::{Cage, Frame, Offset};
+    use std::time::Duration;
+    use common::HapticPulse;
+    use haptics::{Haptics, RecordingHaptics};
+    use report::Well;
+
+    #[test]
+    fn focus_follows_reading_order() {
+        let mists = vec![Mist::new(1, Cage::from((0.5, 0.7, -0.2, 0.0, -0.1, 0.0))),
+                         Mist::new(2, Cage::from((0.2, 0.4, 0.0, 0.2, -0.1, 0.0))),
+                         Mist::new(3, Cage::from((-0.3, -0.1, 0.0, 0.2, -0.1, 0.0))),
+                         Mist::new(4, Cage::from((-0.3, -0.1, -0.2, 0.0, -0.1, 0.0)))];
+        assert_eq!(vec![3, 2, 4, 1], focus_order(mists));
+    }
+
+    #[test]
+    fn tab_cycles_focus_in_order() {
+        let ids = vec![2, 5, 9];
+        assert_eq!(Some(2), next_focus(&ids, None, false));
+        assert_eq!(Some(9), next_focus(&ids, None, true));
+        assert_eq!(Some(9), next_focus(&ids, Some(5), false));
+        assert_eq!(Some(2), next_focus(&ids, Some(9), false));
+        assert_eq!(Some(9), next_focus(&ids, Some(2), true));
+        assert_eq!(Some(2), next_focus(&ids, Some(4), false));
+        assert_eq!(None, next_focus(&Vec::new(), Some(4), false));
+    }
+
+    // Counts a button's clicks and shows the count as the id of a patch.
+    #[derive(Clone)]
+    struct ClickCounter {
+        button: WailStar<ButtonMsg>,
+    }
+
+    impl Star for ClickCounter {
+        type Mdl = (WailStarModel<ButtonMsg>, u64);
+        type Msg = WailingIn;
+        type Out = ();
+
+        fn init(&self) -> (WailStarModel<ButtonMsg>, u64) {
+            (self.button.init(), 0)
+        }
+
+        fn view(&self, model: &(WailStarModel<ButtonMsg>, u64)) -> Vision<WailingIn> {
+            let mut vision = self.button.view(&model.0);
+            vision.add_patch(Patch::new(1000 + model.1, 0.0, 0.1, 0.0, 0.1, -1.0, [1.0; 4], Sigil::Fill));
+            vision
+        }
+
+        fn update(&self, model: &(WailStarModel<ButtonMsg>, u64), message: &WailingIn) -> (WailStarModel<ButtonMsg>, u64) {
+            let button = self.button.update(&model.0, message);
+            let clicks = button.outs.iter().filter(|out| **out == ButtonMsg::Clicked).count() as u64;
+            (button, model.1 + clicks)
+        }
+    }
+
+    #[test]
+    fn dwelling_on_a_button_clicks_it() {
+        let mut model = init();
+        let counter = ClickCounter { button: button_wail("OK", Frame::from((0.2, 0.1, 0.05))).to_star(Offset::default()) };
+        let id = model.summoner.summon(&mut model.id_source, &counter);
+        let ray = Ray { origin: [0.0, 0.0, 1.0], direction: [0.0, 0.0, -1.0] };
+        let start = Instant::now();
+        let model = gaze(model, ray, start);
+        let model = gaze(model, ray, start + Duration::from_millis(gaze::DEFAULT_DWELL_MILLIS / 2));
+        let model = gaze(model, ray, start + Duration::from_millis(gaze::DEFAULT_DWELL_MILLIS));
+        let model = gaze(model, ray, start + Duration::from_millis(gaze::DEFAULT_DWELL_MILLIS * 2));
+        let sight = model.summoner.get_demon_box_clone(id).unwrap().see();
+        assert!(sight.patches().contains_key(&1001));
+    }
+
+    fn buzz() -> HapticPulse {
+        HapticPulse { hand: 0, strength: 0.8, duration: Duration::from_millis(30) }
+    }
+
+    // Asks for a buzz on the hand each time it senses one.
+    #[derive(Clone)]
+    struct Buzzer;
+
+    impl Star for Buzzer {
+        type Mdl = bool;
+        type Msg = ();
+        type Out = ();
+
+        fn init(&self) -> bool { false }
+
+        fn view(&self, _: &bool) -> Vision<()> {
+            let mut vision = Vision::new();
+            vision.add_mist(Mist::new(3, Cage::from((-0.1, 0.1, -0.1, 0.1, -0.1, 0.1))), |wish| {
+                match wish {
+                    Wish::SenseHand(_) => Some(()),
+                    _ => None,
+                }
+            });
+            vision
+        }
+
+        fn update(&self, _: &bool, _: &()) -> bool { true }
+
+        fn report<T>(&self, sensed: &bool, well: &mut Well<(), T>) {
+            if *sensed {
+                well.add_wish(Wish::HapticPulse(buzz()));
+            }
+        }
+    }
+
+    #[test]
+    fn reported_pulses_reach_the_recorder() {
+        let viewer = Viewer::start();
+        let mut model = init();
+        model.summoner.summon(&mut model.id_source, &Buzzer);
+        let model = step(Message::SetHand(Hand::default()), model, &viewer).unwrap();
+        let mut haptics = RecordingHaptics::default();
+        for pulse in viewer.take_haptics() {
+            haptics.pulse(pulse);
+        }
+        step(Message::EmitAnimationFrame, model, &viewer).unwrap();
+        let later = viewer.take_haptics();
+        viewer.stop();
+        assert_eq!(vec![buzz()], haptics.pulses);
+        assert!(later.is_empty());
+    }
+
+    // Lights the scene until a hand touches it.
+    #[derive(Clone)]
+    struct Lamp;
+
+    impl Star for Lamp {
+        type Mdl = bool;
+        type Msg = ();
+        type Out = ();
+
+        fn init(&self) -> bool { true }
+
+        fn view(&self, lit: &bool) -> Vision<()> {
+            let mut vision = Vision::new();
+            vision.add_mist(Mist::new(4, Cage::from((-0.1, 0.1, -0.1, 0.1, -0.1, 0.1))), |wish| {
+                match wish {
+                    Wish::SenseHand(_) => Some(()),
+                    _ => None,
+                }
+            });
+            if *lit {
+                vision.set_lighting(Lighting::unlit());
+            }
+            vision
+        }
+
+        fn update(&self, _: &bool, _: &()) -> bool { false }
+    }
+
+    #[test]
+    fn lighting_returns_to_default_when_no_star_sets_it() {
+        let viewer = Viewer::start();
+        let mut model = init();
+        model.summoner.summon(&mut model.id_source, &Lamp);
+        view(&model, &viewer);
+        let lit = viewer.get_scene().lighting;
+        step(Message::SetHand(Hand::default()), model, &viewer).unwrap();
+        let unlit = viewer.get_scene().lighting;
+        viewer.stop();
+        assert_eq!(Lighting::unlit(), lit);
+        assert_eq!(Lighting::default(), unlit);
+    }
+}
@@ -15,10 +15,21 @@ impl Camera {
         Camera { eye: [0.0, 1.6, 0.0], look: [0.0, 0.0, -1.0], up: [0.0, 1.0, 0.0] }
     }
 
+    // Behind and above the player, looking down past them at the scene.
+    pub fn spectator() -> Camera {
+        Camera { eye: [0.0, 2.2, 1.5], look: [0.0, -0.4, -1.0], up: [0.0, 1.0, 0.0] }
+    }
+
     pub fn get_view_and_projection<T>(&self, surface: &T) -> ([[f32; 4]; 4], [[f32; 4]; 4]) where T: Surface {
-        let view = mat::view_matrix(&self.eye, &self.look, &self.up);
-        let perspective = mat::perspective_matrix(surface.get_dimensions(), PI / 3.0);
-        (view, perspective)
+        (self.get_view(), self.get_projection(surface.get_dimensions()))
+    }
+
+    pub fn get_view(&self) -> [[f32; 4]; 4] {
+        mat::view_matrix(&self.eye, &self.look, &self.up)
+    }
+
+    pub fn get_projection(&self, dimensions: (u32, u32)) -> [[f32; 4]; 4] {
+        mat::perspective_matrix(dimensions, PI / 3.0)
     }
 
     pub fn move_up(&self) -> Camera {
@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 pub const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
 pub const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
 pub const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
@@ -7,3 +9,305 @@ pub const YELLOW: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
 pub const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
 pub const GRAY: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
 pub const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+const CSS_NAMES: [(&'static str, u32); 32] = [
+    ("black", 0x000000), ("silver", 0xc0c0c0), ("gray", 0x808080), ("grey", 0x808080),
+    ("white", 0xffffff), ("maroon", 0x800000), ("red", 0xff0000), ("purple", 0x800080),
+    ("fuchsia", 0xff00ff), ("magenta", 0xff00ff), ("green", 0x008000), ("lime", 0x00ff00),
+    ("olive", 0x808000), ("yellow", 0xffff00), ("navy", 0x000080), ("blue", 0x0000ff),
+    ("teal", 0x008080), ("aqua", 0x00ffff), ("cyan", 0x00ffff), ("orange", 0xffa500),
+    ("pink", 0xffc0cb), ("brown", 0xa52a2a), ("gold", 0xffd700), ("indigo", 0x4b0082),
+    ("violet", 0xee82ee), ("coral", 0xff7f50), ("salmon", 0xfa8072), ("khaki", 0xf0e68c),
+    ("turquoise", 0x40e0d0), ("lavender", 0xe6e6fa), ("crimson", 0xdc143c), ("tomato", 0xff6347),
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseColorError(pub String);
+
+impl From<[f32; 4]> for Color {
+    fn from(rgba: [f32; 4]) -> Self {
+        Color { r: rgba[0], g: rgba[1], b: rgba[2], a: rgba[3] }
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        color.to_array()
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    // Accepts a css name or hex digits in #rgb, #rgba, #rrggbb or #rrggbbaa form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let color_option = if s.starts_with('#') {
+            Color::from_hex(s)
+        } else {
+            Color::from_name(s).or_else(|| Color::from_hex(s))
+        };
+        color_option.ok_or(ParseColorError(s.to_string()))
+    }
+}
+
+impl Color {
+    pub fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Color { r: r, g: g, b: b, a: 1.0 }
+    }
+
+    pub fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Color { r: r, g: g, b: b, a: a }
+    }
+
+    pub fn to_array(&self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    pub fn with_alpha(&self, a: f32) -> Self {
+        Color { a: a, ..*self }
+    }
+
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex.trim_left_matches('#');
+        if !digits.chars().all(|c| c.is_digit(16)) {
+            return None;
+        }
+        let values: Vec<u32> = digits.chars().map(|c| c.to_digit(16).unwrap()).collect();
+        let channels: Vec<u32> = match values.len() {
+            3 | 4 => values.iter().map(|&v| v * 17).collect(),
+            6 | 8 => values.chunks(2).map(|pair| pair[0] * 16 + pair[1]).collect(),
+            _ => return None,
+        };
+        let alpha = if channels.len() == 4 { channels[3] } else { 255 };
+        Some(Color::rgba(channels[0] as f32 / 255.0, channels[1] as f32 / 255.0, channels[2] as f32 / 255.0,
+                         alpha as f32 / 255.0))
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if name == "transparent" {
+            return Some(Color::rgba(0.0, 0.0, 0.0, 0.0));
+        }
+        CSS_NAMES.iter().find(|&&(css_name, _)| css_name == name).map(|&(_, rgb)| {
+            Color::rgb(((rgb >> 16) & 0xff) as f32 / 255.0, ((rgb >> 8) & 0xff) as f32 / 255.0,
+                       (rgb & 0xff) as f32 / 255.0)
+        })
+    }
+
+    pub fn to_hex(&self) -> String {
+        let byte = |channel: f32| (channel.max(0.0).min(1.0) * 255.0).round() as u32;
+        if self.a < 1.0 {
+            format!("#{:02x}{:02x}{:02x}{:02x}", byte(self.r), byte(self.g), byte(self.b), byte(self.a))
+        } else {
+            format!("#{:02x}{:02x}{:02x}", byte(self.r), byte(self.g), byte(self.b))
+        }
+    }
+
+    // Hue in degrees, saturation and value from 0 to 1.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let chroma = value * saturation;
+        let (r, g, b) = hue_to_rgb(hue, chroma);
+        let m = value - chroma;
+        Color::rgb(r + m, g + m, b + m)
+    }
+
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (max, min) = (self.max_channel(), self.min_channel());
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        (self.hue(), saturation, max)
+    }
+
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let (r, g, b) = hue_to_rgb(hue, chroma);
+        let m = lightness - chroma / 2.0;
+        Color::rgb(r + m, g + m, b + m)
+    }
+
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (max, min) = (self.max_channel(), self.min_channel());
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * lightness - 1.0).abs()) };
+        (self.hue(), saturation, lightness)
+    }
+
+    pub fn lighten(&self, amount: f32) -> Self {
+        let (hue, saturation, lightness) = self.to_hsl();
+        Color::from_hsl(hue, saturation, (lightness + amount).max(0.0).min(1.0)).with_alpha(self.a)
+    }
+
+    pub fn darken(&self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    // Straight interpolation from self at 0 to other at 1, alpha included.
+    pub fn mix(&self, other: &Color, t: f32) -> Self {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Color::rgba(lerp(self.r, other.r), lerp(self.g, other.g), lerp(self.b, other.b), lerp(self.a, other.a))
+    }
+
+    pub fn to_linear(&self) -> Self {
+        Color::rgba(srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b), self.a)
+    }
+
+    pub fn to_srgb(&self) -> Self {
+        Color::rgba(linear_to_srgb(self.r), linear_to_srgb(self.g), linear_to_srgb(self.b), self.a)
+    }
+
+    pub fn rotate_hue(&self, degrees: f32) -> Self {
+        let (hue, saturation, value) = self.to_hsv();
+        Color::from_hsv(hue + degrees, saturation, value).with_alpha(self.a)
+    }
+
+    pub fn complementary(&self) -> Self {
+        self.rotate_hue(180.0)
+    }
+
+    // Count colors centered on this one, each spread degrees of hue from the next.
+    pub fn analogous(&self, count: usize, spread: f32) -> Vec<Self> {
+        let start = -spread * (count as f32 - 1.0) / 2.0;
+        (0..count).map(|i| self.rotate_hue(start + spread * i as f32)).collect()
+    }
+
+    // Steps colors evenly around the hue wheel starting at this one.
+    pub fn hue_wheel(&self, steps: usize) -> Vec<Self> {
+        let step = 360.0 / steps as f32;
+        (0..steps).map(|i| self.rotate_hue(step * i as f32)).collect()
+    }
+
+    fn max_channel(&self) -> f32 {
+        self.r.max(self.g).max(self.b)
+    }
+
+    fn min_channel(&self) -> f32 {
+        self.r.min(self.g).min(self.b)
+    }
+
+    fn hue(&self) -> f32 {
+        let (max, min) = (self.max_channel(), self.min_channel());
+        let delta = max - min;
+        if delta == 0.0 {
+            return 0.0;
+        }
+        let hue = if max == self.r {
+            60.0 * (((self.g - self.b) / delta) % 6.0)
+        } else if max == self.g {
+            60.0 * ((self.b - self.r) / delta + 2.0)
+        } else {
+            60.0 * ((self.r - self.g) / delta + 4.0)
+        };
+        if hue < 0.0 { hue + 360.0 } else { hue }
+    }
+}
+
+fn hue_to_rgb(hue: f32, chroma: f32) -> (f32, f32, f32) {
+    let sector = (((hue % 360.0) + 360.0) % 360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    }
+}
+
+fn srgb_to_linear(channel: f32) -> f32 {
+    if channel <= 0.04045 { channel / 12.92 } else { ((channel + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(channel: f32) -> f32 {
+    if channel <= 0.0031308 { channel * 12.92 } else { 1.055 * channel.powf(1.0 / 2.4) - 0.055 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: Color, actual: Color) {
+        let (e, a) = (expected.to_array(), actual.to_array());
+        for i in 0..4 {
+            assert!((e[i] - a[i]).abs() < 1e-3, "expected {:?}, got {:?}", expected, actual);
+        }
+    }
+
+    #[test]
+    fn converts_to_and_from_arrays() {
+        let color = Color::from(MAGENTA);
+        assert_eq!(Color::rgb(1.0, 0.0, 1.0), color);
+        let array: [f32; 4] = color.into();
+        assert_eq!(MAGENTA, array);
+    }
+
+    #[test]
+    fn parses_hex_forms() {
+        assert_eq!(Some(Color::from(RED)), Color::from_hex("#f00"));
+        assert_eq!(Some(Color::from(CYAN)), Color::from_hex("00ffff"));
+        assert_eq!(Some(Color::rgba(0.0, 0.0, 1.0, 0.0)), Color::from_hex("#0000ff00"));
+        assert_eq!(None, Color::from_hex("#12345"));
+        assert_eq!(None, Color::from_hex("#ggg"));
+        assert_eq!("#ff8000", Color::rgb(1.0, 0.5, 0.0).to_hex());
+    }
+
+    #[test]
+    fn parses_css_names() {
+        assert_eq!(Ok(Color::from(YELLOW)), "Yellow".parse::<Color>());
+        assert_eq!(Ok(Color::from(WHITE)), " #fff ".parse::<Color>());
+        assert_eq!(Err(ParseColorError("chartreuse-ish".to_string())), "chartreuse-ish".parse::<Color>());
+    }
+
+    #[test]
+    fn hsv_round_trips() {
+        let orange = Color::rgb(1.0, 0.5, 0.0);
+        let (h, s, v) = orange.to_hsv();
+        assert!((h - 30.0).abs() < 1e-3);
+        assert_eq!((1.0, 1.0), (s, v));
+        assert_close(orange, Color::from_hsv(h, s, v));
+        assert_close(Color::from(BLUE), Color::from_hsv(240.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn hsl_round_trips() {
+        let teal = Color::rgb(0.2, 0.6, 0.6);
+        let (h, s, l) = teal.to_hsl();
+        assert!((h - 180.0).abs() < 1e-3);
+        assert!((l - 0.4).abs() < 1e-3);
+        assert_close(teal, Color::from_hsl(h, s, l));
+    }
+
+    #[test]
+    fn lighten_darken_and_mix() {
+        assert_close(Color::from(WHITE), Color::from(GRAY).lighten(0.6));
+        assert_close(Color::from(BLACK), Color::from(GRAY).darken(0.6));
+        assert_close(Color::rgb(0.5, 0.0, 0.5), Color::from(RED).mix(&Color::from(BLUE), 0.5));
+    }
+
+    #[test]
+    fn srgb_round_trips() {
+        let gray = Color::from(GRAY);
+        assert!((gray.to_linear().r - 0.214).abs() < 1e-3);
+        assert_close(gray, gray.to_linear().to_srgb());
+    }
+
+    #[test]
+    fn palettes_step_around_the_hue() {
+        assert_close(Color::from(CYAN), Color::from(RED).complementary());
+        let wheel = Color::from(RED).hue_wheel(3);
+        assert_close(Color::from(GREEN), wheel[1]);
+        assert_close(Color::from(BLUE), wheel[2]);
+        let analogous = Color::from(GREEN).analogous(3, 120.0);
+        assert_close(Color::from(RED), analogous[0]);
+        assert_close(Color::from(GREEN), analogous[1]);
+        assert_close(Color::from(BLUE), analogous[2]);
+    }
+}
@@ -4,6 +4,7 @@ extern crate cage;
 
 use cage::Cage;
 use hand::Hand;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum Error {
@@ -11,14 +12,52 @@ pub enum Error {
     NoCompositor,
 }
 
-#[derive(Clone, Debug)]
+// KeyPress, ReceiveChar and Focus only reach the mist holding keyboard focus. HapticPulse goes
+// the other way: stars add it to their Well when reporting, and the runtime passes it to the hand.
+#[derive(Copy, Clone, Debug)]
 pub enum Wish {
     Tick,
     FitToCage(Cage),
     SenseHand(Hand),
+    KeyPress(KeyPress),
+    ReceiveChar(char),
+    Focus(bool),
+    GazeSelect,
+    HapticPulse(HapticPulse),
 }
 
-#[derive(Debug)]
+// Strength runs from 0 to 1. The hand is a Hand's index.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HapticPulse {
+    pub hand: usize,
+    pub strength: f32,
+    pub duration: Duration,
+}
+
+// Keys that mean something to text editing, or the scancode of any other.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum KeyCode {
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Backspace,
+    Delete,
+    Enter,
+    Tab,
+    Escape,
+    Other(u8),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KeyPress {
+    pub code: KeyCode,
+    pub shift: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct RenderSize {
     pub width: u32,
     pub height: u32
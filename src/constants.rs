@@ -1,3 +1,2 @@
-pub const CLEAR_COLOR: (f32, f32, f32, f32) = (0.05, 0.05, 0.08, 1.0);
 pub const CLEAR_DEPTH: f32 = 1.0;
 
@@ -0,0 +1,85 @@
+use cage::Cage;
+use mat;
+use patch::{self, Patch};
+use line_program::{self, LineVertex};
+
+const LABEL_CHAR_WIDTH: f32 = 0.015;
+const LABEL_CHAR_HEIGHT: f32 = 0.025;
+
+// Diagnostic geometry a star can add to its vision. It is drawn only while the host's
+// debug mode is on and never receives wishes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DebugDraw {
+    Line { from: [f32; 3], to: [f32; 3], color: [f32; 4] },
+    Point { at: [f32; 3], size: f32, color: [f32; 4] },
+    Axes { at: [f32; 3], size: f32 },
+    Cage { cage: Cage, color: [f32; 4] },
+    Label { at: [f32; 3], text: String, color: [f32; 4] },
+}
+
+pub fn get_lines(debug_draws: &Vec<DebugDraw>) -> Vec<LineVertex> {
+    let mut vertices = Vec::new();
+    for debug_draw in debug_draws {
+        match debug_draw {
+            &DebugDraw::Line { from, to, color } => {
+                vertices.extend(line_program::line(from, to, color));
+            },
+            &DebugDraw::Point { at, size, color } => {
+                let half = size / 2.0;
+                let (x, y, z) = (at[0], at[1], at[2]);
+                vertices.extend(line_program::line([x - half, y, z], [x + half, y, z], color));
+                vertices.extend(line_program::line([x, y - half, z], [x, y + half, z], color));
+                vertices.extend(line_program::line([x, y, z - half], [x, y, z + half], color));
+            },
+            &DebugDraw::Axes { at, size } => {
+                vertices.extend(line_program::axes(&mat::translation(at[0], at[1], at[2]), size));
+            },
+            &DebugDraw::Cage { ref cage, color } => {
+                vertices.extend(line_program::cage_lines(cage, color));
+            },
+            &DebugDraw::Label { .. } => (),
+        }
+    }
+    vertices
+}
+
+pub fn get_labels(debug_draws: &Vec<DebugDraw>) -> Vec<Patch> {
+    let mut labels = Vec::new();
+    for debug_draw in debug_draws {
+        if let &DebugDraw::Label { at, ref text, color } = debug_draw {
+            labels.extend(patch::text_patches(0, text, at[0], at[1], at[2], LABEL_CHAR_WIDTH, LABEL_CHAR_HEIGHT, color)
+                .into_iter().map(|label| label.with_emissive(true)));
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cage::Cage;
+    use color::{RED, WHITE};
+
+    #[test]
+    fn each_shape_becomes_line_segments() {
+        let debug_draws = vec![
+            DebugDraw::Line { from: [0.0, 0.0, 0.0], to: [1.0, 0.0, 0.0], color: RED },
+            DebugDraw::Point { at: [0.0, 0.0, 0.0], size: 0.1, color: RED },
+            DebugDraw::Axes { at: [0.0, 0.0, 0.0], size: 0.1 },
+            DebugDraw::Cage { cage: Cage::default(), color: RED },
+            DebugDraw::Label { at: [0.0, 0.0, 0.0], text: "hi".to_string(), color: WHITE },
+        ];
+        assert_eq!(2 + 6 + 6 + 24, get_lines(&debug_draws).len());
+    }
+
+    #[test]
+    fn labels_become_letter_patches() {
+        let debug_draws = vec![
+            DebugDraw::Label { at: [0.5, 0.25, 0.0], text: "a b".to_string(), color: WHITE },
+        ];
+        let labels = get_labels(&debug_draws);
+        assert_eq!(2, labels.len());
+        assert_eq!(0.5, labels[0].position.left);
+        assert_eq!(0.25, labels[1].position.bottom);
+    }
+}
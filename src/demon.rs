@@ -5,6 +5,9 @@ use std::boxed::Box;
 use std::collections::HashMap;
 use common::Wish;
 use std::rc::Rc;
+use light::Lighting;
+use debug::DebugDraw;
+use report::Well;
 
 pub trait Sun {
     fn summon(&mut self);
@@ -15,12 +18,20 @@ pub trait Sun {
 pub trait Sight {
     fn patches(&self) -> &HashMap<u64, Patch>;
     fn mists(&self) -> &HashMap<u64, Mist>;
+    fn lighting(&self) -> Option<&Lighting>;
+    fn debug_draws(&self) -> &Vec<DebugDraw>;
 }
 
 pub trait Flare {
     fn wishes(&self) -> &Vec<Wish>;
 }
 
+impl<Out, Msg> Flare for Well<Out, Msg> {
+    fn wishes(&self) -> &Vec<Wish> {
+        &self.wishes
+    }
+}
+
 impl<T> Sight for Vision<T> {
     fn patches(&self) -> &HashMap<u64, Patch> {
         &self.patches
@@ -28,12 +39,21 @@ impl<T> Sight for Vision<T> {
     fn mists(&self) -> &HashMap<u64, Mist> {
         &self.mists
     }
+    fn lighting(&self) -> Option<&Lighting> {
+        self.lighting.as_ref()
+    }
+    fn debug_draws(&self) -> &Vec<DebugDraw> {
+        &self.debug_draws
+    }
 }
 
 pub trait Demon {
     fn id(&self) -> u64;
     fn see(&self) -> Box<Sight>;
     fn poke(&mut self, wish: Wish) -> DemonResult;
+    fn poke_mist(&mut self, mist_id: u64, wish: Wish) -> DemonResult;
+    // Wishes the star reported since the last take.
+    fn take_wishes(&mut self) -> Vec<Wish>;
     fn clone_and_box(&self) -> Box<Demon>;
 }
 
@@ -5,12 +5,15 @@ use vision::Vision;
 use std::time::Instant;
 use star::Star;
 use std::collections::VecDeque;
+use std::mem;
+use report::Well;
 
 #[derive(Clone)]
 pub struct Demonoid<S: Star> {
     pub id: u64,
     model: S::Mdl,
     star: Rc<S>,
+    wishes: Vec<Wish>,
 }
 
 impl<S: Star> Demonoid<S>
@@ -20,8 +23,15 @@ impl<S: Star> Demonoid<S>
             id: id,
             model: model,
             star: Rc::new((*star).clone()),
+            wishes: Vec::new(),
         }
     }
+    fn set_model(&mut self, model: S::Mdl) {
+        self.model = model;
+        let mut well: Well<S::Out, ()> = Well::default();
+        self.star.as_ref().report(&self.model, &mut well);
+        self.wishes.extend(well.wishes().iter().cloned());
+    }
     fn get_vision(&self) -> Vision<S::Msg> {
         self.star.as_ref().view(&self.model)
     }
@@ -72,9 +82,24 @@ impl<S: Star> Demon for Demonoid<S> where S: 'static {
             let mut queue = VecDeque::from(messages);
             while let Some(ref message) = queue.pop_front() {
                 let new_model = self.star.as_ref().update(&self.model, message);
-                self.model = new_model;
+                self.set_model(new_model);
             }
         }
         DemonResult::Keep
     }
+
+    fn poke_mist(&mut self, mist_id: u64, wish: Wish) -> DemonResult {
+        let vision = self.get_vision();
+        if vision.mists.contains_key(&mist_id) {
+            if let Some(message) = vision.get_message_option(mist_id, wish) {
+                let new_model = self.star.as_ref().update(&self.model, &message);
+                self.set_model(new_model);
+            }
+        }
+        DemonResult::Keep
+    }
+
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        mem::replace(&mut self.wishes, Vec::new())
+    }
 }
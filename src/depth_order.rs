@@ -0,0 +1,88 @@
+use std::cmp::Ordering;
+use patch::Patch;
+use mat;
+
+pub fn is_translucent(patch: &Patch) -> bool {
+    patch.glyph != '\u{0}' || patch.alpha() < 1.0
+}
+
+pub fn split_translucent(patches: &Vec<Patch>) -> (Vec<Patch>, Vec<Patch>) {
+    let mut opaque = Vec::new();
+    let mut translucent = Vec::new();
+    for patch in patches {
+        if is_translucent(patch) {
+            translucent.push(*patch);
+        } else {
+            opaque.push(*patch);
+        }
+    }
+    (opaque, translucent)
+}
+
+// Distance in front of the viewer, larger is farther away. View space looks down -z.
+pub fn view_depth(patch: &Patch, model_view: &[[f32; 4]; 4]) -> f32 {
+    let position = &patch.position;
+    let center = [(position.left + position.right) / 2.0, (position.bottom + position.top) / 2.0, position.near];
+    -mat::transform_point(model_view, &center)[2]
+}
+
+// Orders translucent patches so the farthest draws first. Patches at the same depth keep
+// fills ahead of the letters printed on them.
+pub fn back_to_front(patches: &Vec<Patch>, model_view: &[[f32; 4]; 4]) -> Vec<u64> {
+    let mut ordered: Vec<(f32, &Patch)> = patches.iter()
+                                                 .map(|patch| (view_depth(patch, model_view), patch))
+                                                 .collect();
+    ordered.sort_by(|&(a_depth, a), &(b_depth, b)| {
+        match b_depth.partial_cmp(&a_depth).unwrap_or(Ordering::Equal) {
+            Ordering::Equal => {
+                let (a_letter, b_letter) = (a.glyph != '\u{0}', b.glyph != '\u{0}');
+                match a_letter.cmp(&b_letter) {
+                    Ordering::Equal => a.id.cmp(&b.id),
+                    ordering => ordering,
+                }
+            },
+            ordering => ordering,
+        }
+    });
+    ordered.into_iter().map(|(_, patch)| patch.id).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use patch::{Patch, Sigil};
+    use color::{RED, WHITE};
+    use mat::IDENTITY44;
+
+    #[test]
+    fn letters_and_faded_fills_are_translucent() {
+        let opaque = Patch::new(1, 0.0, 1.0, 0.0, 1.0, 0.0, RED, Sigil::Fill);
+        let faded = opaque.with_opacity(0.5);
+        let letter = Patch::new(2, 0.0, 1.0, 0.0, 1.0, 0.0, WHITE, Sigil::Letter('a'));
+        let (opaque_patches, translucent_patches) = split_translucent(&vec![opaque, faded, letter]);
+        assert_eq!(1, opaque_patches.len());
+        assert_eq!(2, translucent_patches.len());
+    }
+
+    #[test]
+    fn farthest_patch_draws_first() {
+        let near = Patch::new(1, 0.0, 1.0, 0.0, 1.0, -1.0, RED, Sigil::Fill).with_opacity(0.5);
+        let far = Patch::new(2, 0.0, 1.0, 0.0, 1.0, -3.0, RED, Sigil::Fill).with_opacity(0.5);
+        assert_eq!(vec![2, 1], back_to_front(&vec![near, far], &IDENTITY44));
+    }
+
+    #[test]
+    fn order_follows_the_view() {
+        let near = Patch::new(1, 0.0, 1.0, 0.0, 1.0, -1.0, RED, Sigil::Fill).with_opacity(0.5);
+        let far = Patch::new(2, 0.0, 1.0, 0.0, 1.0, -3.0, RED, Sigil::Fill).with_opacity(0.5);
+        let from_behind = mat::view_matrix(&[0.0, 0.0, -5.0], &[0.0, 0.0, 1.0], &[0.0, 1.0, 0.0]);
+        assert_eq!(vec![1, 2], back_to_front(&vec![near, far], &from_behind));
+    }
+
+    #[test]
+    fn letter_draws_after_fill_at_same_depth() {
+        let letter = Patch::new(1, 0.0, 1.0, 0.0, 1.0, -1.0, WHITE, Sigil::Letter('a'));
+        let fill = Patch::new(2, 0.0, 1.0, 0.0, 1.0, -1.0, RED, Sigil::Fill).with_opacity(0.5);
+        assert_eq!(vec![2, 1], back_to_front(&vec![letter, fill], &IDENTITY44));
+    }
+}
@@ -1,31 +1,47 @@
 extern crate glium;
 
-use glium::{Surface, Display, GlObject};
+use glium::{Surface, Display};
 use common::RenderSize;
-use eyebuffers::{EyeBuffers};
+use eyebuffers::{EyeBuffers, EyeBufferSettings};
 use programs::Programs;
 
 pub struct Eye {
     clear_color: (f32, f32, f32, f32),
     clear_depth: f32,
+    render_size: RenderSize,
     pub buffers: EyeBuffers,
     pub projection: [[f32; 4]; 4],
 }
 
 pub fn init(display: &Display,
             render_size: &RenderSize,
+            settings: EyeBufferSettings,
             projection: [[f32; 4]; 4],
             clear_color: (f32, f32, f32, f32), clear_depth: f32) -> Eye {
     Eye {
         clear_color: clear_color,
         clear_depth: clear_depth,
-        buffers: EyeBuffers::new(display, render_size),
+        render_size: *render_size,
+        buffers: EyeBuffers::new(display, render_size, settings),
         projection: projection
     }
 }
 
-pub fn draw<T: Surface>(eye: &Eye, surface: &mut T, programs: &Programs, view_matrix: &[[f32; 4]; 4]) -> usize {
-    surface.clear_color_and_depth(eye.clear_color, eye.clear_depth);
-    programs.draw(surface, view_matrix, &eye.projection);
-    eye.buffers.color.get_id() as usize
-}
\ No newline at end of file
+pub fn draw(eye: &Eye, display: &Display, programs: &Programs, view_matrix: &[[f32; 4]; 4]) -> usize {
+    eye.buffers.draw(display, |surface| {
+        surface.clear_color_and_depth(eye.clear_color, eye.clear_depth);
+        programs.draw(surface, view_matrix, &eye.projection);
+    })
+}
+
+impl Eye {
+    pub fn set_clear_color(&mut self, clear_color: (f32, f32, f32, f32)) {
+        self.clear_color = clear_color;
+    }
+
+    pub fn set_buffer_settings(&mut self, display: &Display, settings: EyeBufferSettings) {
+        if settings != self.buffers.settings {
+            self.buffers = EyeBuffers::new(display, &self.render_size, settings);
+        }
+    }
+}
@@ -1,27 +1,152 @@
 extern crate glium;
 
-use glium::framebuffer::{RenderBuffer, DepthRenderBuffer};
-use glium::{Display};
+use glium::framebuffer::{DepthRenderBuffer, SimpleFrameBuffer};
+use glium::texture::{Texture2d, DepthTexture2dMultisample, Texture2dMultisample};
+use glium::uniforms::MagnifySamplerFilter;
+use glium::{Display, Surface, GlObject, BlitTarget};
 use common::{RenderSize};
 
+const SAMPLE_COUNTS: [u32; 4] = [1, 2, 4, 8];
+const MIN_RENDER_SCALE: f32 = 0.5;
+const MAX_RENDER_SCALE: f32 = 2.0;
+const RENDER_SCALE_STEP: f32 = 0.1;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EyeBufferSettings {
+    pub samples: u32,
+    pub render_scale: f32,
+}
+
+impl Default for EyeBufferSettings {
+    fn default() -> Self {
+        EyeBufferSettings { samples: 4, render_scale: 1.0 }
+    }
+}
+
+impl EyeBufferSettings {
+    pub fn scaled_size(&self, render_size: &RenderSize) -> RenderSize {
+        RenderSize {
+            width: ((render_size.width as f32 * self.render_scale).round() as u32).max(1),
+            height: ((render_size.height as f32 * self.render_scale).round() as u32).max(1),
+        }
+    }
+
+    pub fn with_next_samples(&self) -> Self {
+        let index = SAMPLE_COUNTS.iter().position(|&samples| samples == self.samples).unwrap_or(0);
+        EyeBufferSettings { samples: SAMPLE_COUNTS[(index + 1) % SAMPLE_COUNTS.len()], ..*self }
+    }
+
+    pub fn with_scale_step(&self, steps: i32) -> Self {
+        let render_scale = self.render_scale + steps as f32 * RENDER_SCALE_STEP;
+        EyeBufferSettings { render_scale: render_scale.max(MIN_RENDER_SCALE).min(MAX_RENDER_SCALE), ..*self }
+    }
+}
+
+enum EyeTarget {
+    Direct(DepthRenderBuffer),
+    Multisample(Texture2dMultisample, DepthTexture2dMultisample),
+}
+
+// Eyes draw at the recommended size times the render scale. With more than one sample the
+// scene draws into multisampled buffers that are resolved into the texture we submit.
 pub struct EyeBuffers {
-    pub depth: DepthRenderBuffer,
-    pub color: RenderBuffer,
+    pub settings: EyeBufferSettings,
+    pub size: RenderSize,
+    pub resolve: Texture2d,
+    target: EyeTarget,
 }
 
 impl EyeBuffers {
-    pub fn new(display: &Display, render_size: &RenderSize) -> Self {
-        let depth_buffer: DepthRenderBuffer = DepthRenderBuffer::new(
-            display,
-            glium::texture::DepthFormat::I24,
-            render_size.width, render_size.height).unwrap();
-        let color_buffer: RenderBuffer = RenderBuffer::new(
+    pub fn new(display: &Display, render_size: &RenderSize, settings: EyeBufferSettings) -> Self {
+        let size = settings.scaled_size(render_size);
+        let resolve = Texture2d::empty_with_format(
             display,
             glium::texture::UncompressedFloatFormat::U8U8U8U8,
-            render_size.width, render_size.height).unwrap();
+            glium::texture::MipmapsOption::NoMipmap,
+            size.width, size.height).unwrap();
+        let target = if settings.samples > 1 {
+            let color = Texture2dMultisample::empty(display, size.width, size.height, settings.samples).unwrap();
+            let depth = DepthTexture2dMultisample::empty(display, size.width, size.height, settings.samples).unwrap();
+            EyeTarget::Multisample(color, depth)
+        } else {
+            let depth = DepthRenderBuffer::new(
+                display,
+                glium::texture::DepthFormat::I24,
+                size.width, size.height).unwrap();
+            EyeTarget::Direct(depth)
+        };
         EyeBuffers {
-            depth: depth_buffer,
-            color: color_buffer
+            settings: settings,
+            size: size,
+            resolve: resolve,
+            target: target,
         }
     }
+
+    pub fn draw<F>(&self, display: &Display, draw: F) -> usize where F: FnOnce(&mut SimpleFrameBuffer) {
+        match self.target {
+            EyeTarget::Direct(ref depth) => {
+                let mut frame = SimpleFrameBuffer::with_depth_buffer(display, &self.resolve, depth).unwrap();
+                draw(&mut frame);
+            },
+            EyeTarget::Multisample(ref color, ref depth) => {
+                let mut frame = SimpleFrameBuffer::with_depth_buffer(display, color, depth).unwrap();
+                draw(&mut frame);
+                let resolve_frame = SimpleFrameBuffer::new(display, &self.resolve).unwrap();
+                frame.fill(&resolve_frame, MagnifySamplerFilter::Nearest);
+            },
+        }
+        self.resolve.get_id() as usize
+    }
+
+    // Copies the resolved eye into the region of the target starting at left, keeping its aspect.
+    pub fn blit_to<S: Surface>(&self, target: &S, left: u32, width: u32, height: u32) {
+        let blit_target = fit_blit_target((self.size.width, self.size.height), left, width, height);
+        self.resolve.as_surface().blit_whole_color_to(target, &blit_target, MagnifySamplerFilter::Linear);
+    }
+}
+
+// Largest rectangle with the source's aspect ratio centered in the region starting at left.
+fn fit_blit_target((source_width, source_height): (u32, u32), left: u32, width: u32, height: u32) -> BlitTarget {
+    let scale = (width as f32 / source_width as f32).min(height as f32 / source_height as f32);
+    let (fit_width, fit_height) = ((source_width as f32 * scale) as u32, (source_height as f32 * scale) as u32);
+    BlitTarget {
+        left: left + (width - fit_width) / 2,
+        bottom: (height - fit_height) / 2,
+        width: fit_width as i32,
+        height: fit_height as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::RenderSize;
+
+    #[test]
+    fn scaled_size() {
+        let settings = EyeBufferSettings { samples: 4, render_scale: 1.5 };
+        let size = settings.scaled_size(&RenderSize { width: 1000, height: 800 });
+        assert_eq!((1500, 1200), (size.width, size.height));
+    }
+
+    #[test]
+    fn samples_cycle_back_to_one() {
+        let settings = EyeBufferSettings { samples: 8, render_scale: 1.0 };
+        assert_eq!(1, settings.with_next_samples().samples);
+        assert_eq!(2, settings.with_next_samples().with_next_samples().samples);
+    }
+
+    #[test]
+    fn render_scale_is_clamped() {
+        let settings = EyeBufferSettings { samples: 4, render_scale: 1.9 };
+        assert_eq!(MAX_RENDER_SCALE, settings.with_scale_step(3).render_scale);
+        assert_eq!(MIN_RENDER_SCALE, settings.with_scale_step(-30).render_scale);
+    }
+
+    #[test]
+    fn eye_fits_inside_half_window() {
+        let target = fit_blit_target((1000, 1000), 400, 400, 600);
+        assert_eq!((400, 100, 400, 400), (target.left, target.bottom, target.width, target.height));
+    }
 }
@@ -0,0 +1,200 @@
+extern crate cage;
+
+use cage::Cage;
+use mist::Mist;
+use line_program::{self, LineVertex};
+use programs::SCREEN_TO_WORLD;
+use mat;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_DWELL_MILLIS: u64 = 1000;
+// How far ahead the reticle floats when the gaze hits nothing.
+const REACH: f32 = 2.0;
+const RETICLE_SIZE: f32 = 0.02;
+const RETICLE_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+const PROGRESS_COLOR: [f32; 4] = [0.3, 1.0, 0.6, 1.0];
+
+// A ray in screen space, where mists live. The direction has unit length.
+#[derive(Copy, Clone, Debug)]
+pub struct Ray {
+    pub origin: [f32; 3],
+    pub direction: [f32; 3],
+}
+
+impl Ray {
+    pub fn from_world(origin: [f32; 3], direction: [f32; 3]) -> Self {
+        let to_world = SCREEN_TO_WORLD[3];
+        Ray {
+            origin: [origin[0] - to_world[0], origin[1] - to_world[1], origin[2] - to_world[2]],
+            direction: mat::norm(&direction),
+        }
+    }
+
+    // The way out the front (negative z) of a tracked device, like line_program::pointer.
+    pub fn from_pose(to_world: &[[f32; 4]; 4]) -> Self {
+        let origin = mat::transform_point(to_world, &[0.0, 0.0, 0.0]);
+        let ahead = mat::transform_point(to_world, &[0.0, 0.0, -1.0]);
+        Ray::from_world(origin, [ahead[0] - origin[0], ahead[1] - origin[1], ahead[2] - origin[2]])
+    }
+
+    pub fn point_at(&self, distance: f32) -> [f32; 3] {
+        [self.origin[0] + self.direction[0] * distance,
+         self.origin[1] + self.direction[1] * distance,
+         self.origin[2] + self.direction[2] * distance]
+    }
+}
+
+// Slab test: the distance along the ray to where it enters the cage, or zero from inside.
+pub fn cast(ray: &Ray, cage: &Cage) -> Option<f32> {
+    let (left, right, bottom, top, far, near) = cage.limits();
+    let slabs = [(left, right), (bottom, top), (far, near)];
+    let mut enter = 0.0f32;
+    let mut exit = ::std::f32::INFINITY;
+    for axis in 0..3 {
+        let (low, high) = slabs[axis];
+        let (origin, direction) = (ray.origin[axis], ray.direction[axis]);
+        if direction == 0.0 {
+            if origin < low || origin > high {
+                return None;
+            }
+        } else {
+            let (a, b) = ((low - origin) / direction, (high - origin) / direction);
+            enter = enter.max(a.min(b));
+            exit = exit.min(a.max(b));
+        }
+    }
+    if enter <= exit { Some(enter) } else { None }
+}
+
+// The nearest mist along the ray.
+pub fn nearest_hit<'a, I>(ray: &Ray, mists: I) -> Option<(Mist, f32)> where I: Iterator<Item = &'a Mist> {
+    let mut nearest: Option<(Mist, f32)> = None;
+    for mist in mists {
+        if let Some(distance) = cast(ray, mist.cage()) {
+            if nearest.map_or(true, |(_, nearest_distance)| distance < nearest_distance) {
+                nearest = Some((*mist, distance));
+            }
+        }
+    }
+    nearest
+}
+
+// Gazing at the same mist for its dwell selects it once. Looking away starts over.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GazeDwell {
+    pub target: Option<u64>,
+    since: Option<Instant>,
+    selected: bool,
+}
+
+impl GazeDwell {
+    pub fn sense(&self, target: Option<u64>, dwell: Duration, instant: Instant) -> (GazeDwell, Option<u64>) {
+        if target != self.target {
+            let since = target.map(|_| instant);
+            return (GazeDwell { target: target, since: since, selected: false }, None);
+        }
+        match (target, self.since) {
+            (Some(id), Some(since)) if !self.selected && instant.duration_since(since) >= dwell => {
+                (GazeDwell { selected: true, ..*self }, Some(id))
+            },
+            _ => (*self, None),
+        }
+    }
+
+    // From 0 when the gaze arrives to 1 at selection.
+    pub fn progress(&self, dwell: Duration, instant: Instant) -> f32 {
+        match self.since {
+            Some(_) if self.selected => 1.0,
+            Some(since) => {
+                let elapsed = instant.duration_since(since);
+                let elapsed_millis = elapsed.as_secs() as f32 * 1000.0 + elapsed.subsec_nanos() as f32 / 1e6;
+                let dwell_millis = dwell.as_secs() as f32 * 1000.0 + dwell.subsec_nanos() as f32 / 1e6;
+                if dwell_millis > 0.0 { (elapsed_millis / dwell_millis).min(1.0) } else { 1.0 }
+            },
+            None => 0.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Reticle {
+    pub center: [f32; 3],
+    pub progress: f32,
+}
+
+impl Reticle {
+    pub fn on_ray(ray: &Ray, hit_distance: Option<f32>, progress: f32) -> Self {
+        Reticle { center: ray.point_at(hit_distance.unwrap_or(REACH)), progress: progress }
+    }
+}
+
+// A square around the gaze point with a bar beneath filling as the dwell runs, in screen space.
+pub fn reticle_lines(reticle: &Reticle) -> Vec<LineVertex> {
+    let (x, y, z) = (reticle.center[0], reticle.center[1], reticle.center[2]);
+    let half = RETICLE_SIZE / 2.0;
+    let (left, right, bottom, top) = (x - half, x + half, y - half, y + half);
+    let mut vertices = Vec::new();
+    vertices.extend(line_program::line([left, bottom, z], [right, bottom, z], RETICLE_COLOR));
+    vertices.extend(line_program::line([right, bottom, z], [right, top, z], RETICLE_COLOR));
+    vertices.extend(line_program::line([right, top, z], [left, top, z], RETICLE_COLOR));
+    vertices.extend(line_program::line([left, top, z], [left, bottom, z], RETICLE_COLOR));
+    if reticle.progress > 0.0 {
+        let bar = bottom - half / 2.0;
+        vertices.extend(line_program::line([left, bar, z], [left + RETICLE_SIZE * reticle.progress, bar, z], PROGRESS_COLOR));
+    }
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mist::Mist;
+    use cage::Cage;
+    use std::time::{Duration, Instant};
+
+    fn forward() -> Ray {
+        Ray { origin: [0.0, 0.0, 1.0], direction: [0.0, 0.0, -1.0] }
+    }
+
+    #[test]
+    fn rays_hit_the_near_face() {
+        let cage = Cage::from((-0.1, 0.1, -0.1, 0.1, -0.2, 0.0));
+        assert_eq!(Some(1.0), cast(&forward(), &cage));
+        let beside = Ray { origin: [0.5, 0.0, 1.0], direction: [0.0, 0.0, -1.0] };
+        assert_eq!(None, cast(&beside, &cage));
+        let behind = Ray { origin: [0.0, 0.0, 1.0], direction: [0.0, 0.0, 1.0] };
+        assert_eq!(None, cast(&behind, &cage));
+        let inside = Ray { origin: [0.0, 0.0, -0.1], direction: [1.0, 0.0, 0.0] };
+        assert_eq!(Some(0.0), cast(&inside, &cage));
+        let slanted = Ray::from_world([0.0, 1.6, 0.0], [0.15, 0.0, -1.0]);
+        let hit = cast(&slanted, &Cage::from((0.2, 0.4, -0.1, 0.1, -1.1, -0.9))).unwrap();
+        assert!((slanted.point_at(hit)[2] + 0.9).abs() < 1e-5);
+    }
+
+    #[test]
+    fn nearest_mist_wins() {
+        let mists = vec![Mist::new(1, Cage::from((-0.1, 0.1, -0.1, 0.1, -0.6, -0.5))),
+                         Mist::new(2, Cage::from((-0.1, 0.1, -0.1, 0.1, -0.1, 0.0))),
+                         Mist::new(3, Cage::from((0.2, 0.3, -0.1, 0.1, -0.1, 0.0)))];
+        let (mist, distance) = nearest_hit(&forward(), mists.iter()).unwrap();
+        assert_eq!((2, 1.0), (mist.id(), distance));
+    }
+
+    #[test]
+    fn dwelling_selects_once() {
+        let start = Instant::now();
+        let dwell = Duration::from_millis(500);
+        let (gaze, selected) = GazeDwell::default().sense(Some(4), dwell, start);
+        assert_eq!(None, selected);
+        let (gaze, selected) = gaze.sense(Some(4), dwell, start + Duration::from_millis(250));
+        assert_eq!(None, selected);
+        assert!((gaze.progress(dwell, start + Duration::from_millis(250)) - 0.5).abs() < 1e-3);
+        let (gaze, selected) = gaze.sense(Some(4), dwell, start + Duration::from_millis(500));
+        assert_eq!(Some(4), selected);
+        let (gaze, selected) = gaze.sense(Some(4), dwell, start + Duration::from_millis(1200));
+        assert_eq!(None, selected);
+        let (gaze, _) = gaze.sense(Some(5), dwell, start + Duration::from_millis(1300));
+        let (_, selected) = gaze.sense(Some(5), dwell, start + Duration::from_millis(1600));
+        assert_eq!(None, selected);
+    }
+}
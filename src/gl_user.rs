@@ -11,6 +11,25 @@ use app::{Message as AppMessage};
 use std::sync::mpsc::Sender;
 use std::time::{Instant, Duration};
 use hand::Hand;
+use cage::Offset;
+use eye;
+use eye::Eye;
+use eyebuffers::EyeBufferSettings;
+use stereo::{Stereo, StereoMode};
+use anaglyph_program::AnaglyphProgram;
+use common::{RenderSize, KeyPress};
+use std::borrow::Borrow;
+use theme::Theme;
+use gaze::Ray;
+use haptics::{Haptics, RecordingHaptics};
+
+const CLEAR_DEPTH: f32 = 1.0;
+
+struct StereoEyes {
+    size: RenderSize,
+    left: Eye,
+    right: Eye,
+}
 
 pub struct Model {
     display: Rc<Display>,
@@ -19,7 +38,15 @@ pub struct Model {
     camera: Camera,
     app: Sender<AppMessage>,
     hand: Hand,
+    // The hand that HJKL does not move until SwitchHand trades them.
+    other_hand: Hand,
     viewer: Viewer,
+    stereo: Stereo,
+    stereo_eyes: Option<StereoEyes>,
+    anaglyph_program: AnaglyphProgram,
+    theme: Theme,
+    // The desktop has no controllers to buzz, so pulses are only recorded.
+    haptics: RecordingHaptics,
 }
 
 pub enum Message {
@@ -27,13 +54,22 @@ pub enum Message {
     MoveCamera(Direction),
     MoveHand(Direction),
     ResetCamera,
+    ToggleMists,
+    ToggleDebug,
+    ToggleTheme,
+    CycleStereo,
+    StepIpd(i32),
     EmitAnimationFrame,
+    KeyPress(KeyPress),
+    ReceiveChar(char),
+    SetGrab(bool),
+    SwitchHand,
 }
 
 pub fn run(viewer: Viewer, app: Sender<AppMessage>) {
     let mut model = init(viewer, app);
     loop {
-        let message = draw(&model);
+        let message = draw(&mut model);
         match update(message, model) {
             None => return,
             Some(next_model) => model = next_model,
@@ -50,11 +86,17 @@ pub fn init(viewer: Viewer, app: Sender<AppMessage>) -> Model {
     Model {
         app: app,
         display: display.clone(),
-        programs: Programs::new(display, viewer.clone(), HandType::Keyboard),
+        programs: Programs::new(display.clone(), viewer.clone(), HandType::Keyboard),
         keymap: Keymap::init(),
         camera: Camera::start(),
         hand: Default::default(),
+        other_hand: Hand::at(Offset::from((0.2, 0.0, 0.05))).with_index(1),
         viewer: viewer.clone(),
+        stereo: Default::default(),
+        stereo_eyes: None,
+        anaglyph_program: AnaglyphProgram::new(display.borrow()),
+        theme: Default::default(),
+        haptics: Default::default(),
     }
 }
 
@@ -62,15 +104,58 @@ pub fn update(message: Message, mut model: Model) -> Option<Model> {
     use app::Message as AppMessage;
     match message {
         Message::Quit => None,
-        Message::ResetCamera => Some(model.with_camera(Camera::start())),
+        Message::ResetCamera => {
+            let model = model.with_camera(Camera::start());
+            send_gaze(&model);
+            Some(model)
+        },
         Message::MoveCamera(direction) => {
             let camera = get_camera(&model, direction);
-            Some(model.with_camera(camera))
+            let model = model.with_camera(camera);
+            send_gaze(&model);
+            Some(model)
+        },
+        Message::ToggleMists => {
+            model.programs.toggle_mists();
+            Some(model)
+        },
+        Message::ToggleDebug => {
+            model.programs.toggle_debug();
+            Some(model)
+        },
+        Message::ToggleTheme => {
+            model.theme = model.theme.toggled();
+            if let Some(ref mut eyes) = model.stereo_eyes {
+                eyes.left.set_clear_color(model.theme.clear_color());
+                eyes.right.set_clear_color(model.theme.clear_color());
+            }
+            model.app.send(AppMessage::SetTheme(model.theme)).unwrap();
+            Some(model)
+        },
+        Message::CycleStereo => {
+            model.stereo = model.stereo.with_next_mode();
+            Some(model)
+        },
+        Message::StepIpd(steps) => {
+            model.stereo = model.stereo.with_ipd_step(steps);
+            Some(model)
         },
         Message::EmitAnimationFrame => {
+            for pulse in model.viewer.take_haptics() {
+                model.haptics.pulse(pulse);
+            }
+            send_gaze(&model);
             model.app.send(AppMessage::EmitAnimationFrame).unwrap_or(());
             Some(model)
         },
+        Message::KeyPress(key_press) => {
+            model.app.send(AppMessage::KeyPress(key_press)).unwrap_or(());
+            Some(model)
+        },
+        Message::ReceiveChar(c) => {
+            model.app.send(AppMessage::ReceiveChar(c)).unwrap_or(());
+            Some(model)
+        },
         Message::MoveHand(direction) => {
             const STEP: f32 = 0.05;
             let (dx, dy, dz) = match direction {
@@ -87,9 +172,31 @@ pub fn update(message: Message, mut model: Model) -> Option<Model> {
             model.app.send(AppMessage::SetHand(model.hand)).unwrap();
             Some(model)
         },
+        Message::SetGrab(grab) => {
+            if grab != model.hand.grab {
+                model.hand.grab = grab;
+                model.viewer.set_hand(model.hand);
+                model.app.send(AppMessage::SetHand(model.hand)).unwrap();
+            }
+            Some(model)
+        },
+        Message::SwitchHand => {
+            let hand = model.other_hand;
+            model.other_hand = model.hand;
+            model.hand = hand;
+            model.viewer.set_hand(model.hand);
+            model.app.send(AppMessage::SetHand(model.hand)).unwrap();
+            Some(model)
+        },
     }
 }
 
+// On the desktop the camera's look direction stands in for the headset's.
+fn send_gaze(model: &Model) {
+    let ray = Ray::from_world(model.camera.eye, model.camera.look);
+    model.app.send(AppMessage::SetGaze(ray)).unwrap_or(());
+}
+
 fn get_camera(model: &Model, direction: Direction) -> Camera {
     match direction {
         Direction::Up => model.camera.move_up(),
@@ -101,20 +208,21 @@ fn get_camera(model: &Model, direction: Direction) -> Camera {
     }
 }
 
-pub fn draw(model: &Model) -> Message {
-    let mut target = model.display.draw();
-    target.clear_color_and_depth((0.0, 0.0, 0.0, 1.0), 1.0);
-    let (view, perspective) = model.camera.get_view_and_projection(&target);
-    model.programs.draw(&mut target, &view, &perspective);
-    target.finish().unwrap();
+pub fn draw(model: &mut Model) -> Message {
+    model.programs.update();
+    match model.stereo.mode {
+        StereoMode::Mono => draw_mono(model),
+        _ => draw_stereo(model),
+    }
 
     let frame_instant = Instant::now();
     let frame_duration = Duration::from_millis(300);
 
+    let typing = model.viewer.get_focus().is_some();
     let mut message_option: Option<Message> = None;
     while message_option.is_none() {
         for glutin_event in model.display.poll_events() {
-            if let Some(key) = model.keymap.key_for_event(&glutin_event) {
+            if let Some(key) = model.keymap.key_for_event(&glutin_event, typing) {
                 message_option = message_option_from_key(key);
                 if message_option.is_some() {
                     break;
@@ -128,6 +236,61 @@ pub fn draw(model: &Model) -> Message {
     return message_option.unwrap();
 }
 
+fn draw_mono(model: &Model) {
+    let mut target = model.display.draw();
+    target.clear_color_and_depth(model.theme.clear_color(), CLEAR_DEPTH);
+    let (view, perspective) = model.camera.get_view_and_projection(&target);
+    model.programs.draw(&mut target, &view, &perspective);
+    target.finish().unwrap();
+}
+
+// Draws each eye offscreen through the same path the headset uses, then combines them.
+fn draw_stereo(model: &mut Model) {
+    let eye_size = model.stereo.eye_size(model.display.get_framebuffer_dimensions());
+    let needs_eyes = match model.stereo_eyes {
+        Some(ref eyes) => eyes.size != eye_size,
+        None => true,
+    };
+    if needs_eyes {
+        model.stereo_eyes = Some(init_stereo_eyes(model, eye_size));
+    }
+
+    let display: &Display = model.display.borrow();
+    let (left_view, right_view) = model.stereo.eye_views(&model.camera.get_view());
+    if let Some(ref eyes) = model.stereo_eyes {
+        eye::draw(&eyes.left, display, &model.programs, &left_view);
+        eye::draw(&eyes.right, display, &model.programs, &right_view);
+
+        let mut target = display.draw();
+        target.clear_color_and_depth(model.theme.clear_color(), CLEAR_DEPTH);
+        let (width, height) = target.get_dimensions();
+        match model.stereo.mode {
+            StereoMode::SideBySide => {
+                let half_width = width / 2;
+                eyes.left.buffers.blit_to(&target, 0, half_width, height);
+                eyes.right.buffers.blit_to(&target, half_width, half_width, height);
+            },
+            StereoMode::Anaglyph => {
+                model.anaglyph_program.draw(&mut target, &eyes.left.buffers.resolve, &eyes.right.buffers.resolve);
+            },
+            StereoMode::Mono => (),
+        }
+        target.finish().unwrap();
+    }
+}
+
+fn init_stereo_eyes(model: &Model, size: RenderSize) -> StereoEyes {
+    let display: &Display = model.display.borrow();
+    let projection = model.camera.get_projection((size.width, size.height));
+    let settings = EyeBufferSettings::default();
+    let clear_color = model.theme.clear_color();
+    StereoEyes {
+        size: size,
+        left: eye::init(display, &size, settings, projection, clear_color, CLEAR_DEPTH),
+        right: eye::init(display, &size, settings, projection, clear_color, CLEAR_DEPTH),
+    }
+}
+
 impl Model {
     pub fn with_camera(self, camera: Camera) -> Self {
         Model {
@@ -137,7 +300,13 @@ impl Model {
             camera: camera,
             app: self.app,
             hand: self.hand,
+            other_hand: self.other_hand,
             viewer: self.viewer,
+            stereo: self.stereo,
+            stereo_eyes: self.stereo_eyes,
+            anaglyph_program: self.anaglyph_program,
+            theme: self.theme,
+            haptics: self.haptics,
         }
     }
 }
@@ -158,6 +327,17 @@ fn message_option_from_key(key: Key) -> Option<Message> {
         Key::L => Some(Message::MoveHand(Direction::Right)),
         Key::RBracket => Some(Message::MoveHand(Direction::Far)),
         Key::LBracket => Some(Message::MoveHand(Direction::Near)),
+        Key::ToggleMists => Some(Message::ToggleMists),
+        Key::ToggleDebug => Some(Message::ToggleDebug),
+        Key::ToggleTheme => Some(Message::ToggleTheme),
+        Key::CycleStereo => Some(Message::CycleStereo),
+        Key::IpdDown => Some(Message::StepIpd(-1)),
+        Key::IpdUp => Some(Message::StepIpd(1)),
+        Key::Press(key_press) => Some(Message::KeyPress(key_press)),
+        Key::Char(c) => Some(Message::ReceiveChar(c)),
+        Key::Grab(grab) => Some(Message::SetGrab(grab)),
+        Key::SwitchHand => Some(Message::SwitchHand),
+        Key::CycleMirror | Key::CycleSamples | Key::RenderScaleDown | Key::RenderScaleUp => None,
     }
 }
 
@@ -2,22 +2,80 @@ extern crate cage;
 
 use cage::Offset;
 
+// Columns of a rotation matrix, as in the controller's to-world matrix.
+pub type Rotation = [[f32; 3]; 3];
+
 #[derive(Copy, Clone, Debug)]
 pub struct Hand {
-    pub offset: Offset
+    // Tells hands apart when more than one is tracked. The first hand is 0.
+    pub index: usize,
+    pub offset: Offset,
+    // Held while the grab input is down: a controller's trigger or grip, or Space on the desktop.
+    pub grab: bool,
+    // Only tracked controllers know which way the hand is turned.
+    pub rotation: Option<Rotation>,
 }
 
 impl Default for Hand {
     fn default() -> Self {
-        Hand { offset: Offset { x: 0.0, y: 0.0, z: 0.05 } }
+        Hand::at(Offset { x: 0.0, y: 0.0, z: 0.05 })
     }
 }
 
 impl Hand {
+    pub fn at(offset: Offset) -> Self {
+        Hand { index: 0, offset: offset, grab: false, rotation: None }
+    }
+    pub fn with_index(&self, index: usize) -> Self {
+        Hand { index: index, ..*self }
+    }
+    pub fn with_grab(&self, grab: bool) -> Self {
+        Hand { grab: grab, ..*self }
+    }
+    pub fn with_rotation(&self, rotation: Option<Rotation>) -> Self {
+        Hand { rotation: rotation, ..*self }
+    }
     pub fn plus_offset(&self, offset: &Offset) -> Self {
-        Hand { offset: self.offset.shift(offset.x, offset.y, offset.z) }
+        Hand { offset: self.offset.shift(offset.x, offset.y, offset.z), ..*self }
     }
     pub fn minus_offset(&self, offset: &Offset) -> Self {
-        Hand { offset: self.offset.shift(-offset.x, -offset.y, -offset.z) }
+        Hand { offset: self.offset.shift(-offset.x, -offset.y, -offset.z), ..*self }
+    }
+}
+
+// The hand a single-hand control follows. A hand that engages the control keeps it until it
+// lets go, and samples from other hands are ignored meanwhile.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct HandLock {
+    index: Option<usize>,
+}
+
+impl HandLock {
+    pub fn is_held(&self) -> bool {
+        self.index.is_some()
+    }
+    pub fn admits(&self, hand: &Hand) -> bool {
+        self.index.map_or(true, |index| index == hand.index)
+    }
+    pub fn follow(&self, hand: &Hand, engaged: bool) -> Self {
+        HandLock { index: if engaged { Some(hand.index) } else { None } }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_admits_only_the_engaged_hand_until_it_lets_go() {
+        let (first, second) = (Hand::default(), Hand::default().with_index(1));
+        let lock = HandLock::default();
+        assert!(lock.admits(&first) && lock.admits(&second));
+        let lock = lock.follow(&first, true);
+        assert!(lock.is_held());
+        assert!(lock.admits(&first) && !lock.admits(&second));
+        let lock = lock.follow(&first, false);
+        assert!(!lock.is_held());
+        assert!(lock.admits(&second));
     }
 }
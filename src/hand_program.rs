@@ -4,7 +4,7 @@ use glium::{Surface, VertexBuffer, Program, Display};
 use glium::index::{NoIndices, PrimitiveType};
 use std::rc::Rc;
 use std::borrow::Borrow;
-use viewer::Viewer;
+use scene::Scene;
 
 #[derive(Copy, Clone)]
 struct Vertex {
@@ -16,41 +16,40 @@ pub struct HandProgram {
     display: Rc<Display>,
     program: glium::Program,
     indices: glium::index::NoIndices,
-    viewer: Viewer,
     model_matrix: [[f32; 4]; 4],
 }
 
 impl HandProgram {
-    pub fn new(display: Rc<Display>, viewer: Viewer) -> Self {
+    pub fn new(display: Rc<Display>) -> Self {
         use programs::SCREEN_TO_WORLD;
         HandProgram {
             display: display.clone(),
             program: Program::from_source(display.borrow() as &Display, VERTEX_SHADER, FRAGMENT_SHADER, None).unwrap(),
             indices: NoIndices(PrimitiveType::LinesList),
-            viewer: viewer,
             model_matrix: SCREEN_TO_WORLD,
         }
     }
 
-    fn get_vertex_buffer(&self) -> VertexBuffer<Vertex> {
+    fn get_vertex_buffer(&self, scene: &Scene) -> VertexBuffer<Vertex> {
         const RADIUS: f32 = 0.05;
-        let hand = self.viewer.get_hand();
-        let center = hand.offset;
-        let (l, r, b, t) = (center.x - RADIUS, center.x + RADIUS, center.y - RADIUS, center.y + RADIUS);
-        let left = Vertex { position: [l, center.y, center.z] };
-        let right = Vertex { position: [r, center.y, center.z] };
-        let bottom = Vertex { position: [center.x, b, center.z] };
-        let top = Vertex { position: [center.x, t, center.z] };
         let mut vertices = Vec::new();
-        vertices.push(left);
-        vertices.push(right);
-        vertices.push(bottom);
-        vertices.push(top);
+        for hand in &scene.hands {
+            let center = hand.offset;
+            let (l, r, b, t) = (center.x - RADIUS, center.x + RADIUS, center.y - RADIUS, center.y + RADIUS);
+            let left = Vertex { position: [l, center.y, center.z] };
+            let right = Vertex { position: [r, center.y, center.z] };
+            let bottom = Vertex { position: [center.x, b, center.z] };
+            let top = Vertex { position: [center.x, t, center.z] };
+            vertices.push(left);
+            vertices.push(right);
+            vertices.push(bottom);
+            vertices.push(top);
+        }
         VertexBuffer::new(self.display.borrow() as &Display, &vertices).unwrap()
     }
 
-    pub fn draw<T: Surface>(&self, surface: &mut T, view: &[[f32; 4]; 4], projection: &[[f32; 4]; 4]) {
-        let vertex_buffer = self.get_vertex_buffer();
+    pub fn draw<T: Surface>(&self, surface: &mut T, scene: &Scene, view: &[[f32; 4]; 4], projection: &[[f32; 4]; 4]) {
+        let vertex_buffer = self.get_vertex_buffer(scene);
         let uniforms = uniform! { model: self.model_matrix, view: * view, perspective: * projection };
         surface.draw(
             &vertex_buffer,
@@ -0,0 +1,101 @@
+use common::HapticPulse;
+use std::time::Instant;
+
+// The longest pulse a controller takes at once.
+pub const MAX_PULSE_MICROS: u16 = 3999;
+
+pub trait Haptics {
+    fn pulse(&mut self, pulse: HapticPulse);
+}
+
+// Pulses a recording keeps before dropping the oldest, so a long desktop session stays small.
+pub const RECORDED_PULSES: usize = 64;
+
+// Stands in for controllers on the desktop and in tests, keeping the latest pulses requested.
+#[derive(Clone, Debug, Default)]
+pub struct RecordingHaptics {
+    pub pulses: Vec<HapticPulse>,
+}
+
+impl Haptics for RecordingHaptics {
+    fn pulse(&mut self, pulse: HapticPulse) {
+        if self.pulses.len() >= RECORDED_PULSES {
+            self.pulses.remove(0);
+        }
+        self.pulses.push(pulse);
+    }
+}
+
+pub fn pulse_micros(strength: f32) -> u16 {
+    (strength.max(0.0).min(1.0) * MAX_PULSE_MICROS as f32).round() as u16
+}
+
+// Controllers buzz in short pulses, so a pulse lasting longer is repeated each frame until its
+// duration runs out. When pulses overlap on a hand the strongest wins.
+#[derive(Clone, Debug, Default)]
+pub struct HapticSchedule {
+    active: Vec<(HapticPulse, Instant)>,
+}
+
+impl HapticSchedule {
+    pub fn add(&mut self, pulse: HapticPulse, instant: Instant) {
+        self.active.push((pulse, instant + pulse.duration));
+    }
+
+    // Hand indices with the micros to pulse them for this frame.
+    pub fn frame(&mut self, instant: Instant) -> Vec<(usize, u16)> {
+        self.active.retain(|&(_, until)| until > instant);
+        let mut frame: Vec<(usize, u16)> = Vec::new();
+        for &(pulse, _) in &self.active {
+            let micros = pulse_micros(pulse.strength);
+            match frame.iter().position(|&(hand, _)| hand == pulse.hand) {
+                Some(position) => frame[position].1 = frame[position].1.max(micros),
+                None => frame.push((pulse.hand, micros)),
+            }
+        }
+        frame.sort_by_key(|&(hand, _)| hand);
+        frame
+    }
+}
+
+impl Haptics for HapticSchedule {
+    fn pulse(&mut self, pulse: HapticPulse) {
+        self.add(pulse, Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::HapticPulse;
+    use std::time::{Duration, Instant};
+
+    fn pulse(hand: usize, strength: f32, millis: u64) -> HapticPulse {
+        HapticPulse { hand: hand, strength: strength, duration: Duration::from_millis(millis) }
+    }
+
+    #[test]
+    fn recording_keeps_pulses_in_order() {
+        let mut haptics = RecordingHaptics::default();
+        haptics.pulse(pulse(1, 0.5, 20));
+        haptics.pulse(pulse(0, 1.0, 100));
+        assert_eq!(vec![pulse(1, 0.5, 20), pulse(0, 1.0, 100)], haptics.pulses);
+        for millis in 0..RECORDED_PULSES as u64 {
+            haptics.pulse(pulse(0, 0.5, millis));
+        }
+        assert_eq!(RECORDED_PULSES, haptics.pulses.len());
+        assert_eq!(pulse(0, 0.5, 0), haptics.pulses[0]);
+    }
+
+    #[test]
+    fn schedule_repeats_pulses_until_they_end() {
+        let start = Instant::now();
+        let mut schedule = HapticSchedule::default();
+        schedule.add(pulse(1, 0.5, 50), start);
+        schedule.add(pulse(0, 0.25, 30), start);
+        schedule.add(pulse(1, 1.0, 20), start);
+        assert_eq!(vec![(0, 1000), (1, MAX_PULSE_MICROS)], schedule.frame(start));
+        assert_eq!(vec![(1, 2000)], schedule.frame(start + Duration::from_millis(40)));
+        assert_eq!(Vec::<(usize, u16)>::new(), schedule.frame(start + Duration::from_millis(50)));
+    }
+}
@@ -4,37 +4,99 @@ use glium::backend::glutin_backend::GlutinFacade;
 use vr::System;
 use eye;
 use eye::Eye;
-use constants::{CLEAR_COLOR, CLEAR_DEPTH};
+use eyebuffers::EyeBufferSettings;
+use constants::CLEAR_DEPTH;
 use programs::Programs;
+use cam::Camera;
+use theme::Theme;
 use glium::Surface;
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MirrorMode {
+    LeftEye,
+    SideBySide,
+    Spectator,
+}
+
+impl MirrorMode {
+    pub fn next(&self) -> Self {
+        match *self {
+            MirrorMode::LeftEye => MirrorMode::SideBySide,
+            MirrorMode::SideBySide => MirrorMode::Spectator,
+            MirrorMode::Spectator => MirrorMode::LeftEye,
+        }
+    }
+}
+
 pub struct Hmd<'a> {
     vr: &'a System,
+    clear_color: (f32, f32, f32, f32),
     pub left_eye: Eye,
     pub right_eye: Eye,
 }
 
 impl<'a> Hmd<'a> {
-    pub fn new(window: &GlutinFacade, vr: &'a System) -> Self {
+    pub fn new(window: &GlutinFacade, vr: &'a System, settings: EyeBufferSettings) -> Self {
         let render_size = vr.get_render_size();
+        let clear_color = Theme::default().clear_color();
         let (left_eye, right_eye) = (
-            eye::init(window, &render_size, vr.get_left_projection(), CLEAR_COLOR, CLEAR_DEPTH),
-            eye::init(window, &render_size, vr.get_right_projection(), CLEAR_COLOR, CLEAR_DEPTH)
+            eye::init(window, &render_size, settings, vr.get_left_projection(), clear_color, CLEAR_DEPTH),
+            eye::init(window, &render_size, settings, vr.get_right_projection(), clear_color, CLEAR_DEPTH)
         );
-        Hmd { vr: vr, left_eye: left_eye, right_eye: right_eye }
+        Hmd { vr: vr, clear_color: clear_color, left_eye: left_eye, right_eye: right_eye }
     }
 
-    pub fn draw<T: Surface>(&self, programs: &Programs, view_matrix: &[[f32; 4]; 4],
-                            window: &GlutinFacade, left_surface: &mut T, right_surface: &mut T) {
-        let mut target = window.draw();
-        target.clear_color_and_depth(CLEAR_COLOR, CLEAR_DEPTH);
-        programs.draw(&mut target, view_matrix, &self.left_eye.projection);
-        target.finish().unwrap();
+    pub fn get_buffer_settings(&self) -> EyeBufferSettings {
+        self.left_eye.buffers.settings
+    }
+
+    pub fn set_buffer_settings(&mut self, window: &GlutinFacade, settings: EyeBufferSettings) {
+        self.left_eye.set_buffer_settings(window, settings);
+        self.right_eye.set_buffer_settings(window, settings);
+    }
 
+    pub fn set_clear_color(&mut self, clear_color: (f32, f32, f32, f32)) {
+        self.clear_color = clear_color;
+        self.left_eye.set_clear_color(clear_color);
+        self.right_eye.set_clear_color(clear_color);
+    }
+
+    pub fn draw(&self, programs: &Programs, view_matrix: &[[f32; 4]; 4], window: &GlutinFacade,
+                mirror_mode: MirrorMode, spectator: &Camera) {
         let (left_texture, right_texture) = (
-            eye::draw(&self.left_eye, left_surface, programs, view_matrix),
-            eye::draw(&self.right_eye, right_surface, programs, view_matrix)
+            eye::draw(&self.left_eye, window, programs, view_matrix),
+            eye::draw(&self.right_eye, window, programs, view_matrix)
         );
         self.vr.submit_textures(left_texture, right_texture);
+
+        let mut target = window.draw();
+        target.clear_color_and_depth(self.clear_color, CLEAR_DEPTH);
+        let (width, height) = target.get_dimensions();
+        match mirror_mode {
+            MirrorMode::LeftEye => {
+                self.left_eye.buffers.blit_to(&target, 0, width, height);
+            },
+            MirrorMode::SideBySide => {
+                let half_width = width / 2;
+                self.left_eye.buffers.blit_to(&target, 0, half_width, height);
+                self.right_eye.buffers.blit_to(&target, half_width, half_width, height);
+            },
+            MirrorMode::Spectator => {
+                let (view, projection) = spectator.get_view_and_projection(&target);
+                programs.draw(&mut target, &view, &projection);
+                programs.draw_markers(&mut target, &view, &projection);
+            },
+        }
+        target.finish().unwrap();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirror_modes_cycle() {
+        assert_eq!(MirrorMode::LeftEye, MirrorMode::LeftEye.next().next().next());
+    }
+}
@@ -6,7 +6,7 @@ use vision::Vision;
 use cage::Cage;
 use patch::{Patch, Sigil};
 use mist::Mist;
-use color::WHITE;
+use theme::Paint;
 
 #[derive(Clone, Debug)]
 pub struct MistyStar {
@@ -21,7 +21,7 @@ pub fn misty(id: u64, cage: Cage) -> MistyStar {
         id: id,
         cage: cage,
         sub_star: Howl::new(rand::random::<u64>(),
-                            WHITE,
+                            Paint::Text,
                             Cage::from((-0.7, -0.5, 0.25, 0.45, 0.25, 0.25)),
                             Sigil::Letter('S')),
     }
@@ -86,7 +86,7 @@ impl Star for MistyStar {
 #[derive(Clone, Debug)]
 pub struct Howl {
     id: u64,
-    color: [f32; 4],
+    color: Paint,
     cage: Cage,
     sigil: Sigil
 }
@@ -111,14 +111,14 @@ impl Star for Howl {
 
     fn view(&self, model: &Cage) -> Vision<Message> {
         let mut vision = Vision::new();
-        vision.add_patch(Patch::from_cage(&model, self.color, self.sigil, self.id));
+        vision.add_patch(Patch::from_cage(&model, self.color.resolve(), self.sigil, self.id));
         vision
     }
 }
 
 impl Howl {
-    pub fn new(id: u64, color: [f32; 4], cage: Cage, sigil: Sigil) -> Howl {
-        Howl { id: id, color: color, cage: cage, sigil: sigil }
+    pub fn new<C>(id: u64, color: C, cage: Cage, sigil: Sigil) -> Howl where C: Into<Paint> {
+        Howl { id: id, color: color.into(), cage: cage, sigil: sigil }
     }
 }
 
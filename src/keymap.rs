@@ -1,5 +1,6 @@
 use os::is_windows;
-use glium::glutin::{Event, ElementState};
+use common::{KeyPress, KeyCode};
+use glium::glutin::{Event, ElementState, VirtualKeyCode};
 
 pub enum Key {
     LookUp,
@@ -15,10 +16,32 @@ pub enum Key {
     L,
     RBracket,
     LBracket,
+    ToggleMists,
+    ToggleDebug,
+    ToggleTheme,
+    CycleMirror,
+    CycleStereo,
+    IpdDown,
+    IpdUp,
+    CycleSamples,
+    RenderScaleDown,
+    RenderScaleUp,
     Quit,
+    Press(KeyPress),
+    Char(char),
+    Grab(bool),
+    SwitchHand,
 }
 
 impl Key {
+    // Function-key actions stay with the viewer while a mist is taking text.
+    fn is_function(&self) -> bool {
+        match self {
+            &Key::ToggleMists | &Key::ToggleDebug | &Key::ToggleTheme | &Key::CycleMirror | &Key::CycleStereo
+            | &Key::CycleSamples | &Key::RenderScaleDown | &Key::RenderScaleUp | &Key::SwitchHand => true,
+            _ => false,
+        }
+    }
     fn for_windows(glutin_event: &Event) -> Option<Self> {
         match glutin_event {
             &Event::Closed => Some(Key::Quit),
@@ -30,10 +53,17 @@ impl Key {
             &Event::KeyboardInput(ElementState::Pressed, 31, _) => Some(Key::LookDown),
             &Event::KeyboardInput(ElementState::Pressed, 16, _) => Some(Key::LookFar),
             &Event::KeyboardInput(ElementState::Pressed, 18, _) => Some(Key::LookNear),
-            &Event::KeyboardInput(ElementState::Pressed, code, _) => {
-                println!("{}", code);
-                None
-            },
+            &Event::KeyboardInput(ElementState::Pressed, 12, _) => Some(Key::IpdDown),
+            &Event::KeyboardInput(ElementState::Pressed, 13, _) => Some(Key::IpdUp),
+            &Event::KeyboardInput(ElementState::Pressed, 59, _) => Some(Key::ToggleMists),
+            &Event::KeyboardInput(ElementState::Pressed, 60, _) => Some(Key::ToggleDebug),
+            &Event::KeyboardInput(ElementState::Pressed, 61, _) => Some(Key::CycleMirror),
+            &Event::KeyboardInput(ElementState::Pressed, 62, _) => Some(Key::CycleStereo),
+            &Event::KeyboardInput(ElementState::Pressed, 63, _) => Some(Key::CycleSamples),
+            &Event::KeyboardInput(ElementState::Pressed, 66, _) => Some(Key::ToggleTheme),
+            &Event::KeyboardInput(ElementState::Pressed, 64, _) => Some(Key::RenderScaleDown),
+            &Event::KeyboardInput(ElementState::Pressed, 65, _) => Some(Key::RenderScaleUp),
+            &Event::KeyboardInput(ElementState::Pressed, 67, _) => Some(Key::SwitchHand),
             _ => None
         }
     }
@@ -54,28 +84,76 @@ impl Key {
             &Event::KeyboardInput(ElementState::Pressed, 37, _) => Some(Key::L),
             &Event::KeyboardInput(ElementState::Pressed, 30, _) => Some(Key::RBracket),
             &Event::KeyboardInput(ElementState::Pressed, 33, _) => Some(Key::LBracket),
-            &Event::KeyboardInput(ElementState::Pressed, code, _) => {
-                println!("{}", code);
-                None
-            },
+            &Event::KeyboardInput(ElementState::Pressed, 27, _) => Some(Key::IpdDown),
+            &Event::KeyboardInput(ElementState::Pressed, 24, _) => Some(Key::IpdUp),
+            &Event::KeyboardInput(ElementState::Pressed, 122, _) => Some(Key::ToggleMists),
+            &Event::KeyboardInput(ElementState::Pressed, 120, _) => Some(Key::ToggleDebug),
+            &Event::KeyboardInput(ElementState::Pressed, 99, _) => Some(Key::CycleMirror),
+            &Event::KeyboardInput(ElementState::Pressed, 118, _) => Some(Key::CycleStereo),
+            &Event::KeyboardInput(ElementState::Pressed, 96, _) => Some(Key::CycleSamples),
+            &Event::KeyboardInput(ElementState::Pressed, 100, _) => Some(Key::ToggleTheme),
+            &Event::KeyboardInput(ElementState::Pressed, 97, _) => Some(Key::RenderScaleDown),
+            &Event::KeyboardInput(ElementState::Pressed, 98, _) => Some(Key::RenderScaleUp),
+            &Event::KeyboardInput(ElementState::Pressed, 101, _) => Some(Key::SwitchHand),
             _ => None
         }
     }
 }
 
+fn key_code(scan_code: u8, virtual_key_code: Option<VirtualKeyCode>) -> KeyCode {
+    match virtual_key_code {
+        Some(VirtualKeyCode::Left) => KeyCode::Left,
+        Some(VirtualKeyCode::Right) => KeyCode::Right,
+        Some(VirtualKeyCode::Up) => KeyCode::Up,
+        Some(VirtualKeyCode::Down) => KeyCode::Down,
+        Some(VirtualKeyCode::Home) => KeyCode::Home,
+        Some(VirtualKeyCode::End) => KeyCode::End,
+        Some(VirtualKeyCode::Back) => KeyCode::Backspace,
+        Some(VirtualKeyCode::Delete) => KeyCode::Delete,
+        Some(VirtualKeyCode::Return) => KeyCode::Enter,
+        Some(VirtualKeyCode::Tab) => KeyCode::Tab,
+        Some(VirtualKeyCode::Escape) => KeyCode::Escape,
+        _ => KeyCode::Other(scan_code),
+    }
+}
+
 pub struct Keymap {
     is_windows: bool,
+    shift: bool,
 }
 
 impl Keymap {
     pub fn init() -> Self {
-        Keymap { is_windows: is_windows() }
+        Keymap { is_windows: is_windows(), shift: false }
     }
-    pub fn key_for_event(&self, glutin_event: &Event) -> Option<Key> {
-        if self.is_windows {
-            Key::for_windows(glutin_event)
-        } else {
-            Key::for_mac(glutin_event)
+    // While typing, presses go to the focused mist instead of moving the camera or hand.
+    pub fn key_for_event(&mut self, glutin_event: &Event, typing: bool) -> Option<Key> {
+        match glutin_event {
+            &Event::Closed => Some(Key::Quit),
+            &Event::ReceivedCharacter(c) => {
+                if typing && !c.is_control() { Some(Key::Char(c)) } else { None }
+            },
+            &Event::KeyboardInput(state, _, Some(VirtualKeyCode::LShift)) |
+            &Event::KeyboardInput(state, _, Some(VirtualKeyCode::RShift)) => {
+                self.shift = state == ElementState::Pressed;
+                None
+            },
+            &Event::KeyboardInput(state, _, Some(VirtualKeyCode::Space)) if !typing => {
+                Some(Key::Grab(state == ElementState::Pressed))
+            },
+            &Event::KeyboardInput(ElementState::Pressed, scan_code, virtual_key_code) => {
+                let press = Key::Press(KeyPress { code: key_code(scan_code, virtual_key_code), shift: self.shift });
+                let mapped = if self.is_windows {
+                    Key::for_windows(glutin_event)
+                } else {
+                    Key::for_mac(glutin_event)
+                };
+                match mapped {
+                    Some(key) => if typing && !key.is_function() { Some(press) } else { Some(key) },
+                    None => Some(press),
+                }
+            },
+            _ => None,
         }
     }
 }
\ No newline at end of file
@@ -11,8 +11,11 @@ mod hand;
 mod hand_program;
 mod keymap;
 mod patch_program;
+mod patch_slots;
+mod depth_order;
 mod floor_program;
 mod mist_program;
+mod line_program;
 mod controller_program;
 mod programs;
 mod mat;
@@ -20,15 +23,24 @@ mod cam;
 mod gl_user;
 mod vr_user;
 mod eyebuffers;
+mod stereo;
+mod anaglyph_program;
 mod common;
 mod os;
 mod shape;
 mod atlas;
 mod viewer;
+mod scene;
+mod debug;
+mod gaze;
+mod haptics;
 pub mod color;
+pub mod light;
+pub mod theme;
 pub mod scream;
 pub mod howl;
 pub mod roar;
+pub mod text_field;
 mod mist;
 mod patch;
 mod app;
@@ -50,6 +62,7 @@ use std::sync::Arc;
 
 pub use common::IdSource;
 pub use common::Wish;
+pub use common::HapticPulse;
 pub use summoner::Summoner;
 pub use vision::Vision;
 pub use report::Well;
@@ -59,6 +72,7 @@ pub use patch::Patch;
 pub use mist::Mist;
 pub use beat::Beat;
 pub use hand::Hand;
+pub use debug::DebugDraw;
 pub use howl::Howl;
 pub use wail::*;
 
@@ -0,0 +1,99 @@
+use mat;
+
+pub const MAX_LIGHTS: usize = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DirectionalLight {
+    pub direction: [f32; 3],
+    pub color: [f32; 3],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lighting {
+    pub ambient: [f32; 3],
+    pub lights: Vec<DirectionalLight>,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Lighting::new([0.65, 0.65, 0.65])
+            .with_light([-0.3, -0.5, -1.0], [0.35, 0.35, 0.35])
+            .with_light([0.5, 0.2, -0.5], [0.10, 0.10, 0.12])
+    }
+}
+
+impl Lighting {
+    pub fn new(ambient: [f32; 3]) -> Self {
+        Lighting { ambient: ambient, lights: Vec::new() }
+    }
+
+    pub fn unlit() -> Self {
+        Lighting::new([1.0, 1.0, 1.0])
+    }
+
+    // Direction is the way the light travels, from the light toward the scene. Lights beyond
+    // MAX_LIGHTS are dropped.
+    pub fn with_light(&self, direction: [f32; 3], color: [f32; 3]) -> Self {
+        let mut lighting = self.clone();
+        if lighting.lights.len() < MAX_LIGHTS {
+            lighting.lights.push(DirectionalLight { direction: mat::norm(&direction), color: color });
+        }
+        lighting
+    }
+
+    // Lights padded out to MAX_LIGHTS with black so the shader can always sum every slot.
+    pub fn light_slots(&self) -> [DirectionalLight; MAX_LIGHTS] {
+        let dark = DirectionalLight { direction: [0.0, 0.0, -1.0], color: [0.0, 0.0, 0.0] };
+        let mut slots = [dark; MAX_LIGHTS];
+        for (i, light) in self.lights.iter().take(MAX_LIGHTS).enumerate() {
+            slots[i] = *light;
+        }
+        slots
+    }
+
+    pub fn shade(&self, normal: &[f32; 3]) -> [f32; 3] {
+        let normal = mat::norm(normal);
+        let mut shade = self.ambient;
+        for light in &self.lights {
+            let facing = mat::dot(&normal, &mat::neg(&light.direction)).max(0.0);
+            for i in 0..3 {
+                shade[i] += light.color[i] * facing;
+            }
+        }
+        shade
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_slots_pad_with_dark_lights() {
+        let lighting = Lighting::new([0.5, 0.5, 0.5]).with_light([0.0, 0.0, -2.0], [1.0, 1.0, 1.0]);
+        let slots = lighting.light_slots();
+        assert_eq!([0.0, 0.0, -1.0], slots[0].direction);
+        assert_eq!([0.0, 0.0, 0.0], slots[MAX_LIGHTS - 1].color);
+    }
+
+    #[test]
+    fn panels_facing_away_get_only_ambient() {
+        let lighting = Lighting::new([0.25, 0.25, 0.25]).with_light([0.0, 0.0, -1.0], [0.5, 0.5, 0.5]);
+        assert_eq!([0.75, 0.75, 0.75], lighting.shade(&[0.0, 0.0, 1.0]));
+        assert_eq!([0.25, 0.25, 0.25], lighting.shade(&[0.0, 0.0, -1.0]));
+    }
+
+    #[test]
+    fn lights_beyond_the_limit_are_dropped() {
+        let mut lighting = Lighting::new([0.0, 0.0, 0.0]);
+        for _ in 0..MAX_LIGHTS + 2 {
+            lighting = lighting.with_light([0.0, 0.0, -1.0], [0.1, 0.1, 0.1]);
+        }
+        assert_eq!(MAX_LIGHTS, lighting.lights.len());
+    }
+
+    #[test]
+    fn unlit_leaves_color_unchanged() {
+        assert_eq!([1.0, 1.0, 1.0], Lighting::unlit().shade(&[0.0, 1.0, 0.0]));
+    }
+}
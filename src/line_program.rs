@@ -0,0 +1,156 @@
+extern crate glium;
+
+use glium::{Surface, VertexBuffer, Program, Display};
+use glium::index::{NoIndices, PrimitiveType};
+use std::rc::Rc;
+use std::borrow::Borrow;
+use mat;
+use cage::Cage;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+implement_vertex!(LineVertex, position, color);
+
+pub fn line(from: [f32; 3], to: [f32; 3], color: [f32; 4]) -> Vec<LineVertex> {
+    vec![LineVertex { position: from, color: color }, LineVertex { position: to, color: color }]
+}
+
+// The twelve edges of the cage as separate segments.
+pub fn cage_lines(cage: &Cage, color: [f32; 4]) -> Vec<LineVertex> {
+    let (l, r, b, t, f, n) = cage.limits();
+    let corners = [
+        [l, b, f], [r, b, f], [r, t, f], [l, t, f],
+        [l, b, n], [r, b, n], [r, t, n], [l, t, n],
+    ];
+    let edges = [
+        (0, 1), (1, 2), (2, 3), (3, 0),
+        (4, 5), (5, 6), (6, 7), (7, 4),
+        (0, 4), (1, 5), (2, 6), (3, 7),
+    ];
+    let mut vertices = Vec::with_capacity(edges.len() * 2);
+    for &(from, to) in edges.iter() {
+        vertices.extend(line(corners[from], corners[to], color));
+    }
+    vertices
+}
+
+// Red, green and blue lines along the x, y and z axes of the model.
+pub fn axes(model: &[[f32; 4]; 4], length: f32) -> Vec<LineVertex> {
+    let origin = mat::transform_point(model, &[0.0, 0.0, 0.0]);
+    let mut vertices = Vec::new();
+    vertices.extend(line(origin, mat::transform_point(model, &[length, 0.0, 0.0]), [1.0, 0.0, 0.0, 1.0]));
+    vertices.extend(line(origin, mat::transform_point(model, &[0.0, length, 0.0]), [0.0, 1.0, 0.0, 1.0]));
+    vertices.extend(line(origin, mat::transform_point(model, &[0.0, 0.0, length]), [0.0, 0.0, 1.0, 1.0]));
+    vertices
+}
+
+// A line out the front (negative z) of the model, the way tracked devices point.
+pub fn pointer(model: &[[f32; 4]; 4], length: f32, color: [f32; 4]) -> Vec<LineVertex> {
+    let origin = mat::transform_point(model, &[0.0, 0.0, 0.0]);
+    line(origin, mat::transform_point(model, &[0.0, 0.0, -length]), color)
+}
+
+pub struct LineProgram {
+    display: Rc<Display>,
+    program: glium::Program,
+    indices: glium::index::NoIndices,
+}
+
+impl LineProgram {
+    pub fn new(display: Rc<Display>) -> Self {
+        LineProgram {
+            display: display.clone(),
+            program: Program::from_source(display.borrow() as &Display, VERTEX_SHADER, FRAGMENT_SHADER, None).unwrap(),
+            indices: NoIndices(PrimitiveType::LinesList),
+        }
+    }
+
+    pub fn draw<T: Surface>(&self, surface: &mut T, vertices: &Vec<LineVertex>,
+                            model: &[[f32; 4]; 4], view: &[[f32; 4]; 4], projection: &[[f32; 4]; 4]) {
+        if vertices.is_empty() {
+            return;
+        }
+        let vertex_buffer = VertexBuffer::new(self.display.borrow() as &Display, vertices).unwrap();
+        let uniforms = uniform! { model: *model, view: *view, perspective: *projection };
+        surface.draw(
+            &vertex_buffer,
+            &self.indices,
+            &self.program,
+            &uniforms,
+            &glium::DrawParameters {
+                blend: glium::Blend::alpha_blending(),
+                depth: glium::Depth {
+                    test: glium::draw_parameters::DepthTest::IfLess,
+                    write: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        ).unwrap();
+    }
+}
+
+static VERTEX_SHADER: &'static str = r#"
+        #version 140
+
+        in vec3 position;
+        in vec4 color;
+
+        out vec4 v_color;
+
+        uniform mat4 perspective;
+        uniform mat4 view;
+        uniform mat4 model;
+
+        void main() {
+            v_color = color;
+            gl_Position = perspective * view * model * vec4(position, 1.0);
+        }
+    "#;
+
+static FRAGMENT_SHADER: &'static str = r#"
+        #version 140
+
+        in vec4 v_color;
+        out vec4 color;
+
+        void main() {
+            color = v_color;
+        }
+    "#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cage::Cage;
+
+    #[test]
+    fn axes_start_at_model_origin() {
+        let model = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [2.0, 1.0, -3.0, 1.0f32],
+        ];
+        let vertices = axes(&model, 0.5);
+        assert_eq!(6, vertices.len());
+        assert_eq!([2.0, 1.0, -3.0], vertices[0].position);
+        assert_eq!([2.5, 1.0, -3.0], vertices[1].position);
+        assert_eq!([2.0, 1.0, -3.5], pointer(&model, 0.5, [1.0; 4])[1].position);
+    }
+
+    #[test]
+    fn cage_lines_follow_the_edges() {
+        let cage = Cage::from((-1.0, 1.0, -2.0, 2.0, -3.0, 3.0));
+        let vertices = cage_lines(&cage, [1.0; 4]);
+        assert_eq!(24, vertices.len());
+        for pair in vertices.chunks(2) {
+            let (from, to) = (pair[0].position, pair[1].position);
+            let changed = (0..3).filter(|&i| from[i] != to[i]).count();
+            assert_eq!(1, changed);
+        }
+    }
+}
@@ -63,9 +63,80 @@ pub fn add_z(a: &[f32; 3], z: f32) -> [f32; 3] {
     [a[0], a[1], a[2] + z]
 }
 
+// Both matrices are column-major, as glium expects; the result applies b first, then a.
+pub fn multiply(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut m = [[0.0f32; 4]; 4];
+    for column in 0..4 {
+        for row in 0..4 {
+            let mut sum = 0.0;
+            for k in 0..4 {
+                sum += a[k][row] * b[column][k];
+            }
+            m[column][row] = sum;
+        }
+    }
+    m
+}
+
+pub fn multiply33(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut m = [[0.0f32; 3]; 3];
+    for column in 0..3 {
+        for row in 0..3 {
+            let mut sum = 0.0;
+            for k in 0..3 {
+                sum += a[k][row] * b[column][k];
+            }
+            m[column][row] = sum;
+        }
+    }
+    m
+}
+
+// For a rotation, the transpose is the inverse.
+pub fn transpose33(m: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut t = [[0.0f32; 3]; 3];
+    for column in 0..3 {
+        for row in 0..3 {
+            t[column][row] = m[row][column];
+        }
+    }
+    t
+}
+
+pub fn rotate_vector(m: &[[f32; 3]; 3], v: &[f32; 3]) -> [f32; 3] {
+    let mut q = [0.0f32; 3];
+    for row in 0..3 {
+        q[row] = m[0][row] * v[0] + m[1][row] * v[1] + m[2][row] * v[2];
+    }
+    q
+}
+
+pub fn translation(x: f32, y: f32, z: f32) -> [[f32; 4]; 4] {
+    [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [x, y, z, 1.0f32],
+    ]
+}
+
+pub fn transform_point(m: &[[f32; 4]; 4], p: &[f32; 3]) -> [f32; 3] {
+    let mut q = [0.0f32; 3];
+    for row in 0..3 {
+        q[row] = m[0][row] * p[0] + m[1][row] * p[1] + m[2][row] * p[2] + m[3][row];
+    }
+    q
+}
+
 pub static IDENTITY44: [[f32; 4]; 4] = [
     [1.0, 0.0, 0.0, 0.0],
     [0.0, 1.0, 0.0, 0.0],
     [0.0, 0.0, 1.0, 0.0],
     [0.0, 0.0, 0.0, 1.0f32],
 ];
+
+pub static IDENTITY33: [[f32; 3]; 3] = [
+    [1.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0],
+    [0.0, 0.0, 1.0f32],
+];
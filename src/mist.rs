@@ -1,16 +1,42 @@
 use cage::{Cage};
+use std::time::Duration;
+use gaze::DEFAULT_DWELL_MILLIS;
 
+// Focusable mists can take keyboard focus, and with it key presses and characters. Gazing at a
+// mist for its dwell sends it GazeSelect.
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Mist {
     id: u64,
     cage: Cage,
+    focusable: bool,
+    dwell: Option<Duration>,
 }
 
 impl Mist {
-    pub fn new(id: u64, cage: Cage) -> Self { Mist { id: id, cage: cage } }
+    pub fn new(id: u64, cage: Cage) -> Self { Mist { id: id, cage: cage, focusable: false, dwell: None } }
+    pub fn with_focusable(&self, focusable: bool) -> Self { Mist { focusable: focusable, ..*self } }
+    pub fn with_dwell(&self, dwell: Duration) -> Self { Mist { dwell: Some(dwell), ..*self } }
+    pub fn with_cage(&self, cage: Cage) -> Self { Mist { cage: cage, ..*self } }
     pub fn id(&self) -> u64 { self.id }
     pub fn cage(&self) -> &Cage { &self.cage }
+    pub fn is_focusable(&self) -> bool { self.focusable }
+    pub fn dwell(&self) -> Duration { self.dwell.unwrap_or(Duration::from_millis(DEFAULT_DWELL_MILLIS)) }
     pub fn contains(&self, x: f32, y: f32, z: f32) -> bool { self.cage.contains(x, y, z) }
+    pub fn clipped(&self, clip: &Cage) -> Option<Mist> {
+        trim_cage(&self.cage, clip).map(|cage| Mist { cage: cage, ..*self })
+    }
+}
+
+// The overlap of two cages, if they overlap at all.
+pub fn trim_cage(cage: &Cage, clip: &Cage) -> Option<Cage> {
+    let (l, r, b, t, f, n) = cage.limits();
+    let (cl, cr, cb, ct, cf, cn) = clip.limits();
+    let (left, right, bottom, top, far, near) = (l.max(cl), r.min(cr), b.max(cb), t.min(ct), f.max(cf), n.min(cn));
+    if left < right && bottom < top && far <= near {
+        Some(Cage::from((left, right, bottom, top, far, near)))
+    } else {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -25,4 +51,14 @@ mod tests {
         assert!(!mist.contains(0.0, 2.0, 0.0));
         assert!(!mist.contains(2.0, 0.0, 0.0));
     }
+
+    #[test]
+    fn clipping_keeps_only_the_overlap() {
+        let clip = Cage::from((0.0, 1.0, 0.0, 1.0, -0.1, 0.0));
+        let mist = Mist::new(3, Cage::from((0.5, 1.5, -0.5, 0.5, -0.1, 0.0)));
+        let clipped = mist.clipped(&clip).unwrap();
+        assert_eq!(3, clipped.id());
+        assert_eq!((0.5, 1.0, 0.0, 0.5, -0.1, 0.0), clipped.cage().limits());
+        assert!(Mist::new(4, Cage::from((2.0, 3.0, 0.0, 1.0, -0.1, 0.0))).clipped(&clip).is_none());
+    }
 }
\ No newline at end of file
@@ -1,122 +1,134 @@
 extern crate glium;
 
-use glium::{Surface, VertexBuffer, Program, Display};
-use glium::index::{NoIndices, PrimitiveType};
+use glium::{Surface, Display};
 use cage::Cage;
 use std::rc::Rc;
-use std::borrow::Borrow;
-use viewer::Viewer;
+use scene::Scene;
+use hand::Hand;
+use patch::{self, Patch};
+use line_program::{self, LineProgram, LineVertex};
 
-#[derive(Copy, Clone)]
-struct Vertex {
-    position: [f32; 3],
-}
-implement_vertex!(Vertex, position);
+const IDLE_COLOR: [f32; 4] = [0.2, 0.4, 1.0, 0.5];
+const HOVERED_COLOR: [f32; 4] = [1.0, 0.9, 0.2, 0.9];
+const PRESSED_COLOR: [f32; 4] = [1.0, 0.3, 0.2, 1.0];
+const FOCUSED_COLOR: [f32; 4] = [0.3, 1.0, 0.6, 1.0];
+const LABEL_CHAR_WIDTH: f32 = 0.018;
+const LABEL_CHAR_HEIGHT: f32 = 0.03;
 
-pub struct MistProgram {
-    display: Rc<Display>,
-    program: glium::Program,
-    indices: glium::index::NoIndices,
-    model_matrix: [[f32; 4]; 4],
-    viewer: Viewer,
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MistState {
+    Idle,
+    Hovered,
+    Pressed,
 }
 
-impl MistProgram {
-    pub fn new(display: Rc<Display>, viewer: Viewer) -> Self {
-        use programs::SCREEN_TO_WORLD;
-        MistProgram {
-            display: display.clone(),
-            program: Program::from_source(display.borrow() as &Display, VERTEX_SHADER, FRAGMENT_SHADER, None).unwrap(),
-            indices: NoIndices(PrimitiveType::LinesList),
-            model_matrix: SCREEN_TO_WORLD,
-            viewer: viewer,
+impl MistState {
+    fn color(&self) -> [f32; 4] {
+        match *self {
+            MistState::Idle => IDLE_COLOR,
+            MistState::Hovered => HOVERED_COLOR,
+            MistState::Pressed => PRESSED_COLOR,
         }
     }
+}
 
-    fn get_cages(&self) -> Vec<Cage> {
-        let mut cages: Vec<Cage> = Vec::new();
-        let mist_report = self.viewer.get_mists();
-        for (_, mist) in mist_report {
-            cages.push(*mist.cage());
-        }
-        cages
-    }
-
-    fn get_vertex_buffer_option(&self) -> Option<VertexBuffer<Vertex>> {
-        let cages = self.get_cages();
-        if cages.is_empty() {
-            None
+// A hand inside the cage hovers until it pushes past the middle of the cage's depth.
+pub fn mist_state(cage: &Cage, hand: &Hand) -> MistState {
+    let (x, y, z) = hand.offset.tuple();
+    if !cage.contains(x, y, z) {
+        MistState::Idle
+    } else {
+        let (_, _, _, _, far, near) = cage.limits();
+        if z < (far + near) / 2.0 {
+            MistState::Pressed
         } else {
-            let (x, y, z) = self.viewer.get_hand().offset.tuple();
-            let mut vertices = Vec::new();
-            for cage in &cages {
-                if cage.contains(x, y, z) {
-                    let (l, r, b, t, f, n) = cage.limits();
-                    let high_nw = Vertex { position: [l, t, f] };
-                    let high_sw = Vertex { position: [l, t, n] };
-                    let high_ne = Vertex { position: [r, t, f] };
-                    let high_se = Vertex { position: [r, t, n] };
-                    let low_nw = Vertex { position: [l, b, f] };
-                    let low_sw = Vertex { position: [l, b, n] };
-                    let low_ne = Vertex { position: [r, b, f] };
-                    let low_se = Vertex { position: [r, b, n] };
-                    vertices.push(high_nw);
-                    vertices.push(high_sw);
-                    vertices.push(high_ne);
-                    vertices.push(high_se);
-                    vertices.push(low_nw);
-                    vertices.push(low_sw);
-                    vertices.push(low_ne);
-                    vertices.push(low_se);
-                }
-            }
-            Some(VertexBuffer::new(self.display.borrow() as &Display, &vertices).unwrap())
+            MistState::Hovered
         }
     }
+}
 
-    pub fn draw<T: Surface>(&self, surface: &mut T, view: &[[f32; 4]; 4], projection: &[[f32; 4]; 4]) {
-        if let Some(vertex_buffer) = self.get_vertex_buffer_option() {
-            let uniforms = uniform! { model: self.model_matrix, view: *view, perspective: * projection };
-            surface.draw(
-                &vertex_buffer,
-                &self.indices,
-                &self.program,
-                &uniforms,
-                &glium::DrawParameters {
-                    blend: glium::Blend::alpha_blending(),
-                    depth: glium::Depth {
-                        test: glium::draw_parameters::DepthTest::IfLess,
-                        write: true,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                }
-            ).unwrap();
-        }
+// The state from whichever hand is furthest in.
+pub fn hands_state(cage: &Cage, hands: &Vec<Hand>) -> MistState {
+    let states: Vec<MistState> = hands.iter().map(|hand| mist_state(cage, hand)).collect();
+    if states.contains(&MistState::Pressed) {
+        MistState::Pressed
+    } else if states.contains(&MistState::Hovered) {
+        MistState::Hovered
+    } else {
+        MistState::Idle
     }
 }
 
-static VERTEX_SHADER: &'static str = r#"
-        #version 140
+fn get_lines(scene: &Scene) -> Vec<LineVertex> {
+    let mut vertices = Vec::new();
+    for (_, mist) in &scene.mists {
+        let color = if scene.focus == Some(mist.id()) {
+            FOCUSED_COLOR
+        } else {
+            hands_state(mist.cage(), &scene.hands).color()
+        };
+        vertices.extend(line_program::cage_lines(mist.cage(), color));
+    }
+    vertices
+}
 
-        in vec3 position;
+// Each mist's id written just above its top-left corner on the near face.
+pub fn get_labels(scene: &Scene) -> Vec<Patch> {
+    let mut labels = Vec::new();
+    for (_, mist) in &scene.mists {
+        let (left, _, _, top, _, near) = mist.cage().limits();
+        let color = hands_state(mist.cage(), &scene.hands).color();
+        let text = format!("{}", mist.id());
+        labels.extend(patch::text_patches(0, &text, left, top, near, LABEL_CHAR_WIDTH, LABEL_CHAR_HEIGHT, color)
+            .into_iter().map(|label| label.with_emissive(true)));
+    }
+    labels
+}
 
-        uniform mat4 perspective;
-        uniform mat4 view;
-        uniform mat4 model;
+pub struct MistProgram {
+    line_program: LineProgram,
+    model_matrix: [[f32; 4]; 4],
+}
 
-        void main() {
-            mat4 modelview = view * model;
-            gl_Position = perspective * modelview * vec4(position, 1.0);
+impl MistProgram {
+    pub fn new(display: Rc<Display>) -> Self {
+        use programs::SCREEN_TO_WORLD;
+        MistProgram {
+            line_program: LineProgram::new(display),
+            model_matrix: SCREEN_TO_WORLD,
         }
-    "#;
+    }
 
-static FRAGMENT_SHADER: &'static str = r#"
-        #version 140
+    pub fn draw<T: Surface>(&self, surface: &mut T, scene: &Scene, view: &[[f32; 4]; 4], projection: &[[f32; 4]; 4]) {
+        self.line_program.draw(surface, &get_lines(scene), &self.model_matrix, view, projection);
+    }
+}
 
-        out vec4 color;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cage::{Cage, Offset};
+    use hand::Hand;
+    use mist::Mist;
+    use scene::Scene;
 
-        void main() {
-            color = vec4(0.0, 0.0, 1.0, 0.5);
-        }
-    "#;
+    #[test]
+    fn hand_depth_sets_mist_state() {
+        let cage = Cage::from((0.0, 1.0, 0.0, 1.0, -0.2, 0.2));
+        assert_eq!(MistState::Idle, mist_state(&cage, &Hand::at(Offset::from((2.0, 0.5, 0.0)))));
+        assert_eq!(MistState::Hovered, mist_state(&cage, &Hand::at(Offset::from((0.5, 0.5, 0.1)))));
+        assert_eq!(MistState::Pressed, mist_state(&cage, &Hand::at(Offset::from((0.5, 0.5, -0.1)))));
+        let hands = vec![Hand::at(Offset::from((0.5, 0.5, 0.1))), Hand::at(Offset::from((0.5, 0.5, -0.1))).with_index(1)];
+        assert_eq!(MistState::Pressed, hands_state(&cage, &hands));
+    }
+
+    #[test]
+    fn labels_spell_the_mist_id() {
+        let mut scene: Scene = Default::default();
+        scene.mists.insert(42, Mist::new(42, Cage::from((0.0, 1.0, 0.0, 1.0, 0.0, 0.1))));
+        let labels = get_labels(&scene);
+        let glyphs: String = labels.iter().map(|label| label.glyph).collect();
+        assert_eq!("42", glyphs);
+        assert_eq!(1.0, labels[0].position.bottom);
+    }
+}
@@ -1,8 +1,9 @@
 extern crate cage;
 
 use cage::Cage;
+use mat;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct PatchPosition {
     pub left: f32,
     pub right: f32,
@@ -18,6 +19,22 @@ impl PatchPosition {
     }
 }
 
+// The part of a patch's texture that is shown, as fractions of the full texture from its
+// left and bottom edges. Clipping a patch shrinks this along with the patch.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PatchUv {
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub top: f32,
+}
+
+impl Default for PatchUv {
+    fn default() -> Self {
+        PatchUv { left: 0.0, right: 1.0, bottom: 0.0, top: 1.0 }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum Sigil {
     Fill,
@@ -39,11 +56,15 @@ impl Sigil {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Patch {
     pub position: PatchPosition,
     pub color: [f32; 4],
     pub glyph: char,
+    pub opacity: f32,
+    pub emissive: bool,
+    pub uv: PatchUv,
+    pub normal: [f32; 3],
     pub id: u64,
 }
 
@@ -54,11 +75,112 @@ impl Patch {
             position: PatchPosition { left: left, right: right, bottom: bottom, top: top, near: near },
             color: color,
             glyph: sigil.to_glyph(),
+            opacity: 1.0,
+            emissive: false,
+            uv: PatchUv::default(),
+            normal: [0.0, 0.0, 1.0],
         }
     }
     pub fn from_cage(cage: &Cage, color: [f32; 4], sigil: Sigil, id: u64) -> Self {
         Patch {
-            id: id, glyph: sigil.to_glyph(), color: color, position: PatchPosition::from_cage(cage)
+            id: id, glyph: sigil.to_glyph(), color: color, position: PatchPosition::from_cage(cage),
+            opacity: 1.0, emissive: false, uv: PatchUv::default(), normal: [0.0, 0.0, 1.0],
+        }
+    }
+    pub fn with_opacity(&self, opacity: f32) -> Self {
+        Patch { opacity: opacity, ..*self }
+    }
+    pub fn with_emissive(&self, emissive: bool) -> Self {
+        Patch { emissive: emissive, ..*self }
+    }
+    // The way the patch faces for lighting. The patch is still drawn flat; only its shading
+    // follows the normal.
+    pub fn with_normal(&self, normal: [f32; 3]) -> Self {
+        Patch { normal: mat::norm(&normal), ..*self }
+    }
+    pub fn alpha(&self) -> f32 {
+        self.color[3] * self.opacity
+    }
+    // The part of the patch inside the clip, with its texture trimmed to match. Patches lying
+    // in front of or behind the clip, or beside it, are dropped.
+    pub fn clipped(&self, clip: &Cage) -> Option<Patch> {
+        let (clip_left, clip_right, clip_bottom, clip_top, clip_far, clip_near) = clip.limits();
+        let position = self.position;
+        if position.near < clip_far || position.near > clip_near {
+            return None;
         }
+        let left = position.left.max(clip_left);
+        let right = position.right.min(clip_right);
+        let bottom = position.bottom.max(clip_bottom);
+        let top = position.top.min(clip_top);
+        if left >= right || bottom >= top {
+            return None;
+        }
+        let (width, height) = (position.right - position.left, position.top - position.bottom);
+        let (uv_width, uv_height) = (self.uv.right - self.uv.left, self.uv.top - self.uv.bottom);
+        Some(Patch {
+            position: PatchPosition { left: left, right: right, bottom: bottom, top: top, near: position.near },
+            uv: PatchUv {
+                left: self.uv.left + uv_width * (left - position.left) / width,
+                right: self.uv.left + uv_width * (right - position.left) / width,
+                bottom: self.uv.bottom + uv_height * (bottom - position.bottom) / height,
+                top: self.uv.bottom + uv_height * (top - position.bottom) / height,
+            },
+            ..*self
+        })
+    }
+}
+
+// One letter patch per character, laid out left to right from the given corner. Spaces
+// advance the layout without a patch. Patch ids count up from first_id by character index.
+// Letters are emissive so lighting never dims text below legibility.
+pub fn text_patches(first_id: u64, text: &str, left: f32, bottom: f32, near: f32,
+                    char_width: f32, char_height: f32, color: [f32; 4]) -> Vec<Patch> {
+    let mut patches = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let char_left = left + i as f32 * char_width;
+        patches.push(Patch::new(first_id + i as u64, char_left, char_left + char_width, bottom, bottom + char_height, near,
+                                color, Sigil::Letter(c)).with_emissive(true));
+    }
+    patches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cage::Cage;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn clipping_trims_geometry_and_uv() {
+        let patch = Patch::new(1, 0.0, 1.0, 0.0, 2.0, 0.0, [1.0; 4], Sigil::Letter('A'));
+        let clipped = patch.clipped(&Cage::from((0.5, 2.0, -1.0, 1.0, -0.1, 0.1))).unwrap();
+        assert_eq!(PatchPosition { left: 0.5, right: 1.0, bottom: 0.0, top: 1.0, near: 0.0 }, clipped.position);
+        assert!(close(0.5, clipped.uv.left) && close(1.0, clipped.uv.right));
+        assert!(close(0.0, clipped.uv.bottom) && close(0.5, clipped.uv.top));
+        let twice = clipped.clipped(&Cage::from((0.75, 2.0, -1.0, 1.0, -0.1, 0.1))).unwrap();
+        assert!(close(0.75, twice.uv.left) && close(1.0, twice.uv.right));
+    }
+
+    #[test]
+    fn text_is_emissive_and_normals_are_unit_length() {
+        let letters = text_patches(1, "ab", 0.0, 0.0, 0.0, 0.1, 0.1, [1.0; 4]);
+        assert!(letters.iter().all(|patch| patch.emissive));
+        let tilted = Patch::new(1, 0.0, 1.0, 0.0, 1.0, 0.0, [1.0; 4], Sigil::Fill).with_normal([0.0, 3.0, 4.0]);
+        assert!(close(0.6, tilted.normal[1]) && close(0.8, tilted.normal[2]));
+    }
+
+    #[test]
+    fn clipping_drops_patches_outside() {
+        let patch = Patch::new(1, 0.0, 1.0, 0.0, 1.0, 0.0, [1.0; 4], Sigil::Fill);
+        assert!(patch.clipped(&Cage::from((2.0, 3.0, 0.0, 1.0, -0.1, 0.1))).is_none());
+        assert!(patch.clipped(&Cage::from((0.0, 1.0, 0.0, 1.0, 0.1, 0.2))).is_none());
+        assert_eq!(Some(patch), patch.clipped(&Cage::from((-1.0, 2.0, -1.0, 2.0, -0.1, 0.1))));
     }
 }
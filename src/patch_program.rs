@@ -2,13 +2,21 @@ extern crate glium;
 extern crate rusttype;
 extern crate unicode_normalization;
 
-use glium::{Surface, VertexBuffer, Program, Display};
+use glium::{Surface, VertexBuffer, IndexBuffer, Program, Display};
 use glium::index::{NoIndices, PrimitiveType};
-use shape::{Shape, ShapeList, ShapeMask};
+use shape::{Shape, ShapeMask};
 use atlas::{Atlas};
-use viewer::Viewer;
+use patch::Patch;
+use light::Lighting;
+use patch_slots::{PatchSlots, SlotChange};
+use depth_order;
+use mat;
 use std::rc::Rc;
 use std::borrow::Borrow;
+use std::collections::HashMap;
+
+const VERTICES_PER_PATCH: usize = 6;
+const INITIAL_PATCH_CAPACITY: usize = 64;
 
 #[derive(Copy, Clone)]
 struct Vertex {
@@ -17,27 +25,9 @@ struct Vertex {
     color: [f32; 4],
     tex_coords: [f32; 2],
     use_texture: f32,
+    emissive: f32,
 }
-implement_vertex!(Vertex, position, normal, color, tex_coords, use_texture);
-
-fn get_vertices_for_shape_list(shape_list: &ShapeList, atlas: &Atlas) -> Vec<Vertex> {
-    let mut vertices = Vec::new();
-    for shape in shape_list.shapes.iter() {
-        let it: &Shape = shape;
-        if let ShapeMask::None = it.mask {
-            let mut shape_vertices = get_vertices_for_shape(shape, atlas);
-            vertices.append(&mut shape_vertices);
-        }
-    }
-    for shape in shape_list.shapes.iter() {
-        let it: &Shape = shape;
-        if let ShapeMask::Letter(_) = it.mask {
-            let mut shape_vertices = get_vertices_for_shape(shape, atlas);
-            vertices.append(&mut shape_vertices);
-        }
-    }
-    vertices
-}
+implement_vertex!(Vertex, position, normal, color, tex_coords, use_texture, emissive);
 
 fn get_vertex_for_shape(shape: &Shape, position: [f32; 3], tex_coords: [f32; 2]) -> Vertex {
     let use_texture = match shape.mask {
@@ -49,7 +39,8 @@ fn get_vertex_for_shape(shape: &Shape, position: [f32; 3], tex_coords: [f32; 2])
         normal: shape.normal,
         color: shape.color,
         tex_coords: tex_coords,
-        use_texture: use_texture
+        use_texture: use_texture,
+        emissive: if shape.emissive { 1.0 } else { 0.0 },
     }
 }
 
@@ -59,96 +50,258 @@ fn get_vertices_for_shape(shape: &Shape, atlas: &Atlas) -> Vec<Vertex> {
     } else {
         None
     };
-    let (texture_left, texture_right) = match page_option {
+    let (page_left, page_right) = match page_option {
         None => (0.0, 0.0),
         Some(page) => (page.left, page.right),
     };
-    let bottom_left = get_vertex_for_shape(shape, [shape.left, shape.bottom, shape.near], [texture_left, 1.0]);
-    let bottom_right = get_vertex_for_shape(shape, [shape.right, shape.bottom, shape.near], [texture_right, 1.0]);
-    let top_left = get_vertex_for_shape(shape, [shape.left, shape.top, shape.near], [texture_left, 0.0]);
-    let top_right = get_vertex_for_shape(shape, [shape.right, shape.top, shape.near], [texture_right, 0.0]);
+    // Atlas rows run top down, so the shape's bottom edge samples v = 1.
+    let page_width = page_right - page_left;
+    let (texture_left, texture_right) = (page_left + page_width * shape.uv.left, page_left + page_width * shape.uv.right);
+    let (texture_bottom, texture_top) = (1.0 - shape.uv.bottom, 1.0 - shape.uv.top);
+    let bottom_left = get_vertex_for_shape(shape, [shape.left, shape.bottom, shape.near], [texture_left, texture_bottom]);
+    let bottom_right = get_vertex_for_shape(shape, [shape.right, shape.bottom, shape.near], [texture_right, texture_bottom]);
+    let top_left = get_vertex_for_shape(shape, [shape.left, shape.top, shape.near], [texture_left, texture_top]);
+    let top_right = get_vertex_for_shape(shape, [shape.right, shape.top, shape.near], [texture_right, texture_top]);
     vec![bottom_left, top_left, top_right, bottom_left, top_right, bottom_right]
 }
 
+fn get_vertices_for_patch(patch: &Patch, atlas: &Atlas) -> Vec<Vertex> {
+    get_vertices_for_shape(&get_shape(patch), atlas)
+}
+
+fn get_cleared_vertices() -> Vec<Vertex> {
+    let cleared = Vertex {
+        position: [0.0, 0.0, 0.0],
+        normal: [0.0, 0.0, 1.0],
+        color: [0.0, 0.0, 0.0, 0.0],
+        tex_coords: [0.0, 0.0],
+        use_texture: 0.0,
+        emissive: 0.0,
+    };
+    vec![cleared; VERTICES_PER_PATCH]
+}
+
+fn get_cleared_layer(patch_capacity: usize) -> Vec<Vertex> {
+    let mut vertices = Vec::with_capacity(patch_capacity * VERTICES_PER_PATCH);
+    for _ in 0..patch_capacity {
+        vertices.append(&mut get_cleared_vertices());
+    }
+    vertices
+}
+
+// Patches in one layer share a vertex buffer. Each patch keeps its own region of the
+// buffer across frames so only patches that changed since the last frame are uploaded.
+// The index buffer holds the draw order for layers drawn in order and grows with the
+// vertex buffer.
+struct PatchLayer {
+    slots: PatchSlots,
+    vertex_buffer: VertexBuffer<Vertex>,
+    index_buffer: IndexBuffer<u32>,
+}
+
+impl PatchLayer {
+    fn new(display: &Display) -> Self {
+        PatchLayer {
+            slots: PatchSlots::new(),
+            vertex_buffer: VertexBuffer::dynamic(display, &get_cleared_layer(INITIAL_PATCH_CAPACITY)).unwrap(),
+            index_buffer: IndexBuffer::empty_dynamic(display, PrimitiveType::TrianglesList,
+                                                     INITIAL_PATCH_CAPACITY * VERTICES_PER_PATCH).unwrap(),
+        }
+    }
+
+    fn update(&mut self, patches: &HashMap<u64, Patch>, display: &Display, atlas: &Atlas) {
+        let changes = self.slots.sync(patches);
+        let vertex_count = self.slots.len() * VERTICES_PER_PATCH;
+        if vertex_count > self.vertex_buffer.len() {
+            self.grow(vertex_count, display, atlas);
+        } else {
+            for change in changes {
+                match change {
+                    SlotChange::Write(slot, patch) => self.write(slot, &get_vertices_for_patch(&patch, atlas)),
+                    SlotChange::Clear(slot) => self.write(slot, &get_cleared_vertices()),
+                }
+            }
+        }
+    }
+
+    fn grow(&mut self, vertex_count: usize, display: &Display, atlas: &Atlas) {
+        let capacity = vertex_count.max(self.vertex_buffer.len() * 2);
+        let mut vertices = get_cleared_layer(capacity / VERTICES_PER_PATCH);
+        for (slot, patch) in self.slots.patch_slots() {
+            let start = slot * VERTICES_PER_PATCH;
+            for (i, vertex) in get_vertices_for_patch(&patch, atlas).into_iter().enumerate() {
+                vertices[start + i] = vertex;
+            }
+        }
+        self.vertex_buffer = VertexBuffer::dynamic(display, &vertices).unwrap();
+        self.index_buffer = IndexBuffer::empty_dynamic(display, PrimitiveType::TrianglesList, vertices.len()).unwrap();
+    }
+
+    fn write(&mut self, slot: usize, vertices: &[Vertex]) {
+        let start = slot * VERTICES_PER_PATCH;
+        let range = start..(start + VERTICES_PER_PATCH);
+        self.vertex_buffer.slice_mut(range).unwrap().write(vertices);
+    }
+
+    fn draw<T: Surface, U: glium::uniforms::Uniforms>(&self, surface: &mut T, program: &Program, indices: &NoIndices,
+                                                      uniforms: &U, draw_parameters: &glium::DrawParameters) {
+        let vertex_count = self.slots.len() * VERTICES_PER_PATCH;
+        if vertex_count == 0 {
+            return;
+        }
+        let vertex_slice = self.vertex_buffer.slice(0..vertex_count).unwrap();
+        surface.draw(vertex_slice, indices, program, uniforms, draw_parameters).unwrap();
+    }
+
+    fn draw_in_order<T: Surface, U: glium::uniforms::Uniforms>(&self, surface: &mut T, program: &Program, order: &Vec<u64>,
+                                                               uniforms: &U, draw_parameters: &glium::DrawParameters) {
+        let mut indices: Vec<u32> = Vec::with_capacity(order.len() * VERTICES_PER_PATCH);
+        for id in order {
+            if let Some(slot) = self.slots.slot(*id) {
+                let start = slot * VERTICES_PER_PATCH;
+                for index in start..(start + VERTICES_PER_PATCH) {
+                    indices.push(index as u32);
+                }
+            }
+        }
+        if indices.is_empty() {
+            return;
+        }
+        let index_slice = self.index_buffer.slice(0..indices.len()).unwrap();
+        index_slice.write(&indices);
+        surface.draw(&self.vertex_buffer, &index_slice, program, uniforms, draw_parameters).unwrap();
+    }
+}
+
 pub struct PatchProgram {
     program: glium::Program,
     indices: glium::index::NoIndices,
     model_matrix: [[f32; 4]; 4],
     atlas: Atlas,
-    viewer: Viewer,
+    opaque_layer: PatchLayer,
+    translucent_layer: PatchLayer,
+    translucent_patches: Vec<Patch>,
     display: Rc<Display>,
 }
 
 impl PatchProgram {
-    pub fn new(display: Rc<Display>, viewer: Viewer) -> Self {
+    pub fn new(display: Rc<Display>) -> Self {
         use programs::SCREEN_TO_WORLD;
 
-        let program = {
-            let display_ref: &Display = display.borrow();
-            Program::from_source(display_ref, VERTEX_SHADER, FRAGMENT_SHADER, None).unwrap()
-        };
-        let atlas = {
-            let display_ref: &Display = display.borrow();
-            Atlas::new(display_ref)
-        };
+        let display_ref: &Display = display.borrow();
+        let program = Program::from_source(display_ref, VERTEX_SHADER, FRAGMENT_SHADER, None).unwrap();
+        let atlas = Atlas::new(display_ref);
+        let (opaque_layer, translucent_layer) = (PatchLayer::new(display_ref), PatchLayer::new(display_ref));
         PatchProgram {
             program: program,
             indices: NoIndices(PrimitiveType::TrianglesList),
             model_matrix: SCREEN_TO_WORLD,
             atlas: atlas,
-            viewer: viewer,
-            display: display,
+            opaque_layer: opaque_layer,
+            translucent_layer: translucent_layer,
+            translucent_patches: Vec::new(),
+            display: display.clone(),
         }
     }
 
-    pub fn draw<T: Surface>(&self, surface: &mut T, view: &[[f32; 4]; 4], projection: &[[f32; 4]; 4]) {
+    pub fn update(&mut self, patches: &HashMap<u64, Patch>) {
+        let all_patches: Vec<Patch> = patches.values().cloned().collect();
+        let (opaque_patches, translucent_patches) = depth_order::split_translucent(&all_patches);
+        let display: &Display = self.display.borrow();
+        self.opaque_layer.update(&get_patch_map(&opaque_patches), display, &self.atlas);
+        self.translucent_layer.update(&get_patch_map(&translucent_patches), display, &self.atlas);
+        self.translucent_patches = translucent_patches;
+    }
+
+    pub fn draw<T: Surface>(&self, surface: &mut T, lighting: &Lighting,
+                            view: &[[f32; 4]; 4], projection: &[[f32; 4]; 4]) {
+        let lights = lighting.light_slots();
         let uniforms = uniform! {
-            model: self.model_matrix, view: ( *view), perspective: ( * projection),
-            tex: self.atlas.texture.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest)
+            model: self.model_matrix, view: *view, perspective: *projection,
+            tex: self.atlas.texture.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest),
+            ambient: lighting.ambient,
+            light_direction_0: lights[0].direction, light_color_0: lights[0].color,
+            light_direction_1: lights[1].direction, light_color_1: lights[1].color,
+            light_direction_2: lights[2].direction, light_color_2: lights[2].color
+        };
+        let opaque_parameters = glium::DrawParameters {
+            depth: glium::Depth {
+                test: glium::draw_parameters::DepthTest::IfLess,
+                write: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        self.opaque_layer.draw(surface, &self.program, &self.indices, &uniforms, &opaque_parameters);
+
+        // Translucent patches test against the opaque depth but must not hide one another,
+        // so they skip depth writes and rely on back-to-front order for this eye instead.
+        let translucent_parameters = glium::DrawParameters {
+            blend: glium::Blend::alpha_blending(),
+            depth: glium::Depth {
+                test: glium::draw_parameters::DepthTest::IfLess,
+                write: false,
+                ..Default::default()
+            },
+            ..Default::default()
         };
+        let model_view = mat::multiply(view, &self.model_matrix);
+        let order = depth_order::back_to_front(&self.translucent_patches, &model_view);
+        self.translucent_layer.draw_in_order(surface, &self.program, &order, &uniforms, &translucent_parameters);
+    }
 
-        let mut shape_list = ShapeList::new();
-        for shape in get_shapes(&self.viewer) {
-            shape_list.push(shape);
+    // Draws patches that are not part of the scene, such as debug labels, on top of everything
+    // else. They are uploaded fresh each call instead of kept in a layer.
+    pub fn draw_overlay<T: Surface>(&self, surface: &mut T, patches: &Vec<Patch>,
+                                    view: &[[f32; 4]; 4], projection: &[[f32; 4]; 4]) {
+        if patches.is_empty() {
+            return;
+        }
+        let mut vertices = Vec::with_capacity(patches.len() * VERTICES_PER_PATCH);
+        for patch in patches {
+            vertices.append(&mut get_vertices_for_patch(patch, &self.atlas));
         }
         let display: &Display = self.display.borrow();
-        let vertex_buffer = VertexBuffer::new(
-            display,
-            &get_vertices_for_shape_list(&shape_list, &self.atlas)
-        ).unwrap();
-
-        surface.draw(
-            &vertex_buffer,
-            &self.indices,
-            &self.program,
-            &uniforms,
-            &glium::DrawParameters {
-                blend: glium::Blend::alpha_blending(),
-                depth: glium::Depth {
-                    test: glium::draw_parameters::DepthTest::IfLess,
-                    write: true,
-                    ..Default::default()
-                },
-                ..Default::default()
-            }
-        ).unwrap();
+        let vertex_buffer = VertexBuffer::new(display, &vertices).unwrap();
+        let lighting = Lighting::unlit();
+        let lights = lighting.light_slots();
+        let uniforms = uniform! {
+            model: self.model_matrix, view: *view, perspective: *projection,
+            tex: self.atlas.texture.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest),
+            ambient: lighting.ambient,
+            light_direction_0: lights[0].direction, light_color_0: lights[0].color,
+            light_direction_1: lights[1].direction, light_color_1: lights[1].color,
+            light_direction_2: lights[2].direction, light_color_2: lights[2].color
+        };
+        let overlay_parameters = glium::DrawParameters {
+            blend: glium::Blend::alpha_blending(),
+            ..Default::default()
+        };
+        surface.draw(&vertex_buffer, &self.indices, &self.program, &uniforms, &overlay_parameters).unwrap();
     }
 }
 
-fn get_shapes(viewer: &Viewer) -> Vec<Shape> {
-    let patch_map = viewer.get_patches();
-    let mut shapes = Vec::new();
-    for (_, patch) in patch_map {
-        let mask = if patch.glyph == '\u{0}' { ShapeMask::None } else { ShapeMask::Letter(patch.glyph) };
-        let shape = Shape::new(
-            patch.position.left, patch.position.right,
-            patch.position.top, patch.position.bottom,
-            patch.position.near, patch.color,
-            patch.id, mask
-        );
-        shapes.push(shape);
+fn get_patch_map(patches: &Vec<Patch>) -> HashMap<u64, Patch> {
+    let mut patch_map = HashMap::new();
+    for patch in patches {
+        patch_map.insert(patch.id, *patch);
     }
-    shapes
+    patch_map
+}
+
+fn get_shape(patch: &Patch) -> Shape {
+    let mask = if patch.glyph == '\u{0}' { ShapeMask::None } else { ShapeMask::Letter(patch.glyph) };
+    let color = [patch.color[0], patch.color[1], patch.color[2], patch.alpha()];
+    let mut shape = Shape::new(
+        patch.position.left, patch.position.right,
+        patch.position.top, patch.position.bottom,
+        patch.position.near, color,
+        patch.id, mask
+    );
+    shape.emissive = patch.emissive;
+    shape.uv = patch.uv;
+    shape.normal = patch.normal;
+    shape
 }
 
 static VERTEX_SHADER: &'static str = r#"
@@ -159,6 +312,7 @@ static VERTEX_SHADER: &'static str = r#"
         in vec4 color;
         in vec2 tex_coords;
         in float use_texture;
+        in float emissive;
 
         out vec4 vColor;
         out vec2 vTexCoords;
@@ -167,11 +321,24 @@ static VERTEX_SHADER: &'static str = r#"
         uniform mat4 perspective;
         uniform mat4 view;
         uniform mat4 model;
+        uniform vec3 ambient;
+        uniform vec3 light_direction_0;
+        uniform vec3 light_color_0;
+        uniform vec3 light_direction_1;
+        uniform vec3 light_color_1;
+        uniform vec3 light_direction_2;
+        uniform vec3 light_color_2;
 
         void main() {
             mat4 modelview = view * model;
             gl_Position = perspective * modelview * vec4(position, 1.0);
-            vColor = color;
+            vec3 n = normalize(mat3(model) * normal);
+            vec3 shade = ambient
+                + light_color_0 * max(dot(n, -light_direction_0), 0.0)
+                + light_color_1 * max(dot(n, -light_direction_1), 0.0)
+                + light_color_2 * max(dot(n, -light_direction_2), 0.0);
+            vec3 lit = emissive > 0.5 ? color.rgb : min(color.rgb * shade, vec3(1.0));
+            vColor = vec4(lit, color.a);
             vTexCoords = tex_coords;
             vUseTexture = use_texture;
         }
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use patch::Patch;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SlotChange {
+    Write(usize, Patch),
+    Clear(usize),
+}
+
+// Assigns each patch id a stable slot so its vertices can stay in place on the GPU
+// between frames and only changed patches need to be re-uploaded.
+#[derive(Debug)]
+pub struct PatchSlots {
+    slots: HashMap<u64, usize>,
+    patches: HashMap<u64, Patch>,
+    free_slots: Vec<usize>,
+    len: usize,
+}
+
+impl PatchSlots {
+    pub fn new() -> Self {
+        PatchSlots {
+            slots: HashMap::new(),
+            patches: HashMap::new(),
+            free_slots: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn slot(&self, id: u64) -> Option<usize> {
+        self.slots.get(&id).cloned()
+    }
+
+    pub fn patch_slots(&self) -> Vec<(usize, Patch)> {
+        let mut patch_slots = Vec::new();
+        for (id, slot) in &self.slots {
+            patch_slots.push((*slot, self.patches[id]));
+        }
+        patch_slots
+    }
+
+    pub fn sync(&mut self, patches: &HashMap<u64, Patch>) -> Vec<SlotChange> {
+        let mut changes = Vec::new();
+        let removed_ids: Vec<u64> = self.patches.keys()
+                                        .filter(|id| !patches.contains_key(id))
+                                        .cloned()
+                                        .collect();
+        for id in removed_ids {
+            self.patches.remove(&id);
+            if let Some(slot) = self.slots.remove(&id) {
+                self.free_slots.push(slot);
+                changes.push(SlotChange::Clear(slot));
+            }
+        }
+        for (id, patch) in patches {
+            if let Some(old_patch) = self.patches.get(id) {
+                if old_patch == patch {
+                    continue;
+                }
+            }
+            let slot = match self.slots.get(id) {
+                Some(slot) => *slot,
+                None => self.next_slot(),
+            };
+            self.slots.insert(*id, slot);
+            self.patches.insert(*id, *patch);
+            changes.push(SlotChange::Write(slot, *patch));
+        }
+        changes
+    }
+
+    fn next_slot(&mut self) -> usize {
+        if let Some(slot) = self.free_slots.pop() {
+            slot
+        } else {
+            let slot = self.len;
+            self.len = self.len + 1;
+            slot
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use patch::{Patch, Sigil};
+    use color::{RED, GREEN};
+
+    fn patch_map(patches: Vec<Patch>) -> HashMap<u64, Patch> {
+        let mut map = HashMap::new();
+        for patch in patches {
+            map.insert(patch.id, patch);
+        }
+        map
+    }
+
+    #[test]
+    fn unchanged_patches_are_not_rewritten() {
+        let mut slots = PatchSlots::new();
+        let patches = patch_map(vec![Patch::new(1, 0.0, 1.0, 0.0, 1.0, 0.0, RED, Sigil::Fill)]);
+        assert_eq!(1, slots.sync(&patches).len());
+        assert_eq!(0, slots.sync(&patches).len());
+    }
+
+    #[test]
+    fn changed_patch_keeps_its_slot() {
+        let mut slots = PatchSlots::new();
+        slots.sync(&patch_map(vec![
+            Patch::new(1, 0.0, 1.0, 0.0, 1.0, 0.0, RED, Sigil::Fill),
+            Patch::new(2, 0.0, 1.0, 0.0, 1.0, 0.0, RED, Sigil::Fill),
+        ]));
+        let slot = slots.slot(2).unwrap();
+        let green = Patch::new(2, 0.0, 1.0, 0.0, 1.0, 0.0, GREEN, Sigil::Fill);
+        let changes = slots.sync(&patch_map(vec![
+            Patch::new(1, 0.0, 1.0, 0.0, 1.0, 0.0, RED, Sigil::Fill),
+            green,
+        ]));
+        assert_eq!(vec![SlotChange::Write(slot, green)], changes);
+    }
+
+    #[test]
+    fn removed_slot_is_cleared_and_reused() {
+        let mut slots = PatchSlots::new();
+        slots.sync(&patch_map(vec![
+            Patch::new(1, 0.0, 1.0, 0.0, 1.0, 0.0, RED, Sigil::Fill),
+            Patch::new(2, 0.0, 1.0, 0.0, 1.0, 0.0, RED, Sigil::Fill),
+        ]));
+        let slot = slots.slot(1).unwrap();
+        let changes = slots.sync(&patch_map(vec![
+            Patch::new(2, 0.0, 1.0, 0.0, 1.0, 0.0, RED, Sigil::Fill),
+            Patch::new(3, 0.0, 1.0, 0.0, 1.0, 0.0, RED, Sigil::Fill),
+        ]));
+        assert_eq!(SlotChange::Clear(slot), changes[0]);
+        assert_eq!(Some(slot), slots.slot(3));
+        assert_eq!(2, slots.len());
+    }
+}
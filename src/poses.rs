@@ -27,20 +27,30 @@ impl Poses {
         raw4_from_nmatrix4(&nalg_world_to_hmd)
     }
 
+    pub fn get_hmd_to_world_matrix(&self) -> [[f32; 4]; 4] {
+        to_world_matrix(self.get_hmd_pose())
+    }
+
     pub fn get_controller_to_world_matrix(&self) -> Option<[[f32; 4]; 4]> {
         if let Some(ref controller) = self.get_controller_pose() {
-            let s = controller.to_device;
-            Some([
-                [s[0][0], s[1][0], s[2][0], 0.0],
-                [s[0][1], s[1][1], s[2][1], 0.0],
-                [s[0][2], s[1][2], s[2][2], 0.0],
-                [s[0][3], s[1][3], s[2][3], 1.0f32],
-            ])
+            Some(to_world_matrix(controller))
         } else {
             None
         }
     }
 
+    // Device index and to-world matrix of each tracked controller, in device order.
+    pub fn get_controllers(&self) -> Vec<(usize, [[f32; 4]; 4])> {
+        self.poses.poses.iter()
+                        .enumerate()
+                        .filter(|&(_, x)| match x.device_class() {
+                            TrackedDeviceClass::Controller => x.is_valid && x.is_connected,
+                            _ => false
+                        })
+                        .map(|(index, pose)| (index, to_world_matrix(pose)))
+                        .collect()
+    }
+
     pub fn audit(&self) {
         println!("Count {}", self.poses.count);
         let poses: [TrackedDevicePose; 16] = self.poses.poses;
@@ -75,3 +85,13 @@ impl Poses {
     }
 }
 
+
+fn to_world_matrix(pose: &TrackedDevicePose) -> [[f32; 4]; 4] {
+    let s = pose.to_device;
+    [
+        [s[0][0], s[1][0], s[2][0], 0.0],
+        [s[0][1], s[1][1], s[2][1], 0.0],
+        [s[0][2], s[1][2], s[2][2], 0.0],
+        [s[0][3], s[1][3], s[2][3], 1.0f32],
+    ]
+}
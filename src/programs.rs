@@ -1,10 +1,15 @@
 use glium::{Display, Surface};
 use floor_program::FloorProgram;
-use mist_program::MistProgram;
+use mist_program::{self, MistProgram};
 use patch_program::PatchProgram;
 use controller_program::ControllerProgram;
 use hand_program::HandProgram;
+use line_program::{LineProgram, LineVertex};
+use mat::IDENTITY44;
+use debug;
+use gaze;
 use viewer::Viewer;
+use scene::Scene;
 use std::rc::Rc;
 use std::borrow::Borrow;
 
@@ -13,7 +18,13 @@ pub struct Programs {
     mist_program: MistProgram,
     patch_program: PatchProgram,
     controller_program_option: Option<ControllerProgram>,
-    hand_program_option: Option<HandProgram>
+    hand_program_option: Option<HandProgram>,
+    line_program: LineProgram,
+    markers: Vec<LineVertex>,
+    show_mists: bool,
+    show_debug: bool,
+    viewer: Viewer,
+    scene: Scene,
 }
 
 impl Programs {
@@ -23,33 +34,74 @@ impl Programs {
                 let display_ref: &Display = display.borrow();
                 FloorProgram::new(display_ref)
             },
-            mist_program: MistProgram::new(display.clone(), viewer.clone()),
-            patch_program: PatchProgram::new(display.clone(), viewer.clone()),
+            mist_program: MistProgram::new(display.clone()),
+            patch_program: PatchProgram::new(display.clone()),
             controller_program_option: match hand_type {
                 HandType::Vive => Some(ControllerProgram::new(display.borrow())),
                 _ => None
             },
             hand_program_option: match hand_type {
-                HandType::Keyboard => Some(HandProgram::new(display.clone(), viewer.clone())),
+                HandType::Keyboard => Some(HandProgram::new(display.clone())),
                 _ => None
-            }
+            },
+            line_program: LineProgram::new(display.clone()),
+            markers: Vec::new(),
+            show_mists: false,
+            show_debug: false,
+            viewer: viewer,
+            scene: Default::default(),
         }
     }
+
+    // Takes one snapshot of the scene per frame so every eye pass draws the same patches
+    // without asking the viewer again.
+    pub fn update(&mut self) {
+        self.scene = self.viewer.get_scene();
+        self.patch_program.update(&self.scene.patches);
+    }
     pub fn set_controller_model_matrix(&mut self, model_matrix: &Option<[[f32; 4]; 4]>) {
         if let Some(ref mut controller_program) = self.controller_program_option {
             controller_program.set_model_matrix(model_matrix);
         }
     }
 
+    // World-space lines marking tracked devices. Only views from outside the headset draw them.
+    pub fn set_markers(&mut self, markers: Vec<LineVertex>) {
+        self.markers = markers;
+    }
+
+    pub fn draw_markers<T>(&self, surface: &mut T, view: &[[f32; 4]; 4], projection: &[[f32; 4]; 4]) where T: Surface {
+        self.line_program.draw(surface, &self.markers, &IDENTITY44, view, projection);
+    }
+
+    pub fn toggle_mists(&mut self) {
+        self.show_mists = !self.show_mists;
+    }
+
+    pub fn toggle_debug(&mut self) {
+        self.show_debug = !self.show_debug;
+    }
+
     pub fn draw<T>(&self, surface: &mut T, view: &[[f32; 4]; 4], projection: &[[f32; 4]; 4]) where T: Surface {
-        self.mist_program.draw(surface, view, projection);
-        self.patch_program.draw(surface, view, projection);
+        self.patch_program.draw(surface, &self.scene.lighting, view, projection);
         self.floor_program.draw(surface, view, projection);
         if let Some(ref controller_program) = self.controller_program_option {
             controller_program.draw(surface, view, projection);
         }
         if let Some(ref hand_program) = self.hand_program_option {
-            hand_program.draw(surface, view, projection);
+            hand_program.draw(surface, &self.scene, view, projection);
+        }
+        if let Some(ref reticle) = self.scene.reticle {
+            self.line_program.draw(surface, &gaze::reticle_lines(reticle), &SCREEN_TO_WORLD, view, projection);
+        }
+        if self.show_mists {
+            self.mist_program.draw(surface, &self.scene, view, projection);
+            self.patch_program.draw_overlay(surface, &mist_program::get_labels(&self.scene), view, projection);
+        }
+        if self.show_debug {
+            let debug_draws = &self.scene.debug_draws;
+            self.line_program.draw(surface, &debug::get_lines(debug_draws), &SCREEN_TO_WORLD, view, projection);
+            self.patch_program.draw_overlay(surface, &debug::get_labels(debug_draws), view, projection);
         }
     }
 }
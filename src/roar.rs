@@ -4,6 +4,7 @@ use beat::Beat;
 use std::time::{Instant, Duration};
 use common::Wish;
 use star::Star;
+use theme::Paint;
 
 #[derive(Clone, Debug)]
 pub struct Model {
@@ -19,15 +20,15 @@ pub enum Message {
 #[derive(Clone)]
 pub struct Outcome;
 
-pub fn from(colors: Vec<[f32; 4]>) -> RainbowStar {
+pub fn from<C>(colors: Vec<C>) -> RainbowStar where C: Into<Paint> {
     RainbowStar {
-        colors: colors.clone()
+        colors: colors.into_iter().map(|color| color.into()).collect()
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct RainbowStar {
-    colors: Vec<[f32; 4]>
+    colors: Vec<Paint>
 }
 
 impl Star for RainbowStar {
@@ -53,7 +54,7 @@ impl Star for RainbowStar {
 
     fn view(&self, model: &Self::Mdl) -> Vision<Self::Msg> {
         let mut vision = Vision::new();
-        let patch = Patch::new(15674u64, 0.55, 0.65, -0.35, -0.25, 0.25, self.colors[model.index].clone(), Sigil::Fill);
+        let patch = Patch::new(15674u64, 0.55, 0.65, -0.35, -0.25, 0.25, self.colors[model.index].resolve(), Sigil::Fill);
         vision.add_patch(patch);
         let beat = Beat::until_instant(24352u64, model.end_instant);
         vision.add_beat(beat, |wish| match wish {
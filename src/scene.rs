@@ -0,0 +1,18 @@
+use std::collections::HashMap;
+use patch::Patch;
+use mist::Mist;
+use hand::Hand;
+use light::Lighting;
+use debug::DebugDraw;
+use gaze::Reticle;
+
+#[derive(Clone, Debug, Default)]
+pub struct Scene {
+    pub patches: HashMap<u64, Patch>,
+    pub mists: HashMap<u64, Mist>,
+    pub hands: Vec<Hand>,
+    pub lighting: Lighting,
+    pub debug_draws: Vec<DebugDraw>,
+    pub focus: Option<u64>,
+    pub reticle: Option<Reticle>,
+}
@@ -6,6 +6,7 @@ use vision::Vision;
 use cage::Cage;
 use star::Star;
 use patch::Sigil;
+use theme::Paint;
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Model {
@@ -20,7 +21,7 @@ pub enum Message {
 #[derive(Clone, Debug)]
 pub struct Scream {
     id: u64,
-    color: [f32; 4]
+    color: Paint
 }
 
 impl Star for Scream {
@@ -38,13 +39,13 @@ impl Star for Scream {
     fn view(&self, model: &Self::Mdl) -> Vision<Self::Msg> {
         let mut vision = Vision::new();
         if let Some(cage) = model.cage_option {
-            let patch = Patch::from_cage(&cage, self.color, Sigil::Fill, self.id);
+            let patch = Patch::from_cage(&cage, self.color.resolve(), Sigil::Fill, self.id);
             vision.add_patch(patch);
         }
         vision
     }
 }
 
-pub fn new(id: u64, color: [f32; 4]) -> Scream {
-    Scream { id: id, color: color }
+pub fn new<C>(id: u64, color: C) -> Scream where C: Into<Paint> {
+    Scream { id: id, color: color.into() }
 }
@@ -1,3 +1,5 @@
+use patch::PatchUv;
+
 pub enum ShapeMask {
     None,
     Letter(char),
@@ -12,6 +14,8 @@ pub struct Shape {
     pub near: f32,
     pub normal: [f32; 3],
     pub color: [f32; 4],
+    pub emissive: bool,
+    pub uv: PatchUv,
     pub mask: ShapeMask,
 }
 
@@ -23,25 +27,10 @@ impl Shape {
             near: near,
             normal: [0.0, 0.0, 1.0],
             color: color,
+            emissive: false,
+            uv: PatchUv::default(),
             id: id,
             mask: mask,
         }
     }
 }
-
-pub struct ShapeList {
-    pub shapes: Vec<Shape>,
-}
-
-impl ShapeList {
-    pub fn new() -> Self {
-        ShapeList { shapes: Vec::new() }
-    }
-
-    pub fn push(&mut self, shape: Shape) -> u64 {
-        let id = shape.id;
-        self.shapes.push(shape);
-        id
-    }
-}
-
@@ -0,0 +1,93 @@
+use mat;
+use common::RenderSize;
+
+const DEFAULT_IPD: f32 = 0.064;
+const IPD_STEP: f32 = 0.004;
+const MIN_IPD: f32 = 0.0;
+const MAX_IPD: f32 = 0.2;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StereoMode {
+    Mono,
+    SideBySide,
+    Anaglyph,
+}
+
+impl StereoMode {
+    pub fn next(&self) -> Self {
+        match *self {
+            StereoMode::Mono => StereoMode::SideBySide,
+            StereoMode::SideBySide => StereoMode::Anaglyph,
+            StereoMode::Anaglyph => StereoMode::Mono,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stereo {
+    pub mode: StereoMode,
+    pub ipd: f32,
+}
+
+impl Default for Stereo {
+    fn default() -> Self {
+        Stereo { mode: StereoMode::Mono, ipd: DEFAULT_IPD }
+    }
+}
+
+impl Stereo {
+    pub fn with_next_mode(&self) -> Self {
+        Stereo { mode: self.mode.next(), ..*self }
+    }
+
+    // Wider than a real ipd is allowed so small depth differences are easier to spot.
+    pub fn with_ipd_step(&self, steps: i32) -> Self {
+        let ipd = self.ipd + steps as f32 * IPD_STEP;
+        Stereo { ipd: ipd.max(MIN_IPD).min(MAX_IPD), ..*self }
+    }
+
+    // Each eye sits half the ipd to the side of the camera, so its view moves the world
+    // the other way.
+    pub fn eye_views(&self, view: &[[f32; 4]; 4]) -> ([[f32; 4]; 4], [[f32; 4]; 4]) {
+        let half_ipd = self.ipd / 2.0;
+        (mat::multiply(&mat::translation(half_ipd, 0.0, 0.0), view),
+         mat::multiply(&mat::translation(-half_ipd, 0.0, 0.0), view))
+    }
+
+    pub fn eye_size(&self, (width, height): (u32, u32)) -> RenderSize {
+        match self.mode {
+            StereoMode::SideBySide => RenderSize { width: (width / 2).max(1), height: height },
+            _ => RenderSize { width: width, height: height },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mat;
+
+    #[test]
+    fn eyes_see_the_camera_origin_from_either_side() {
+        let stereo = Stereo { mode: StereoMode::Anaglyph, ipd: 0.06 };
+        let (left, right) = stereo.eye_views(&mat::IDENTITY44);
+        let left_x = mat::transform_point(&left, &[0.0, 0.0, -1.0])[0];
+        let right_x = mat::transform_point(&right, &[0.0, 0.0, -1.0])[0];
+        assert!((left_x - 0.03).abs() < 1e-6);
+        assert!((right_x + 0.03).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ipd_is_clamped() {
+        let stereo = Stereo::default();
+        assert_eq!(MIN_IPD, stereo.with_ipd_step(-100).ipd);
+        assert_eq!(MAX_IPD, stereo.with_ipd_step(100).ipd);
+    }
+
+    #[test]
+    fn side_by_side_eyes_take_half_the_window() {
+        let stereo = Stereo { mode: StereoMode::SideBySide, ipd: 0.06 };
+        let size = stereo.eye_size((800, 600));
+        assert_eq!((400, 600), (size.width, size.height));
+    }
+}
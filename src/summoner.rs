@@ -55,6 +55,28 @@ impl Summoner {
             }
         }
     }
+    // Delivers the wish only to the mist with this id, in whichever demon shows it.
+    pub fn update_mist(&mut self, mist_id: u64, wish: Wish) {
+        let mut new_demons = HashMap::new();
+        for (_, demon_box) in &self.demons {
+            let mut new_demon_box = demon_box.clone();
+            match new_demon_box.poke_mist(mist_id, wish.clone()) {
+                DemonResult::Keep => {
+                    new_demons.insert(new_demon_box.id(), new_demon_box);
+                },
+                DemonResult::Remove => (),
+            }
+        }
+        self.demons = new_demons;
+    }
+    // Wishes the demons' stars reported, such as haptic pulses, in no particular order.
+    pub fn take_wishes(&mut self) -> Vec<Wish> {
+        let mut wishes = Vec::new();
+        for (_, demon_box) in self.demons.iter_mut() {
+            wishes.extend(demon_box.take_wishes());
+        }
+        wishes
+    }
     pub fn update(&mut self, wish: Wish) {
         let mut new_demons = HashMap::new();
         for (_, demon_box) in &self.demons {
@@ -75,6 +97,12 @@ impl Summoner {
 mod tests {
     use super::*;
     use demon::Demon;
+    use vision::Vision;
+    use mist::Mist;
+    use patch::{Patch, Sigil};
+    use report::Well;
+    use common::HapticPulse;
+    use std::time::Duration;
 
     #[test]
     fn demons() {
@@ -82,5 +110,62 @@ mod tests {
         let demons: Vec<&Box<Demon>> = summoner.get_demon_boxes();
         assert_eq!(0, demons.len());
     }
-}
 
+    #[derive(Clone)]
+    struct KeyCounter;
+
+    // Shows its count as the id of its one patch.
+    impl Star for KeyCounter {
+        type Mdl = u64;
+        type Msg = ();
+        type Out = ();
+
+        fn init(&self) -> u64 { 0 }
+
+        fn view(&self, count: &u64) -> Vision<()> {
+            let mut vision = Vision::new();
+            vision.add_patch(Patch::new(*count, 0.0, 1.0, 0.0, 1.0, 0.0, [1.0; 4], Sigil::Fill));
+            vision.add_mist(Mist::new(7, Default::default()).with_focusable(true), |wish| {
+                match wish {
+                    Wish::ReceiveChar(_) => Some(()),
+                    _ => None,
+                }
+            });
+            vision
+        }
+
+        fn update(&self, count: &u64, _: &()) -> u64 { count + 1 }
+
+        fn report<T>(&self, count: &u64, well: &mut Well<(), T>) {
+            well.add_wish(Wish::HapticPulse(HapticPulse { hand: 1, strength: *count as f32 / 10.0, duration: Duration::from_millis(20) }));
+        }
+    }
+
+    #[test]
+    fn mist_wishes_reach_only_that_mist() {
+        let mut summoner = Summoner::new();
+        let id = summoner.summon(&mut IdSource::new(), &KeyCounter);
+        summoner.update_mist(8, Wish::ReceiveChar('a'));
+        summoner.update_mist(7, Wish::ReceiveChar('a'));
+        summoner.update(Wish::ReceiveChar('a'));
+        let sight = summoner.get_demon_box_clone(id).unwrap().see();
+        assert!(sight.mists()[&7].is_focusable());
+        assert_eq!(vec![&1], sight.patches().keys().collect::<Vec<&u64>>());
+    }
+
+    #[test]
+    fn reported_wishes_are_taken_once() {
+        let mut summoner = Summoner::new();
+        summoner.summon(&mut IdSource::new(), &KeyCounter);
+        summoner.update_mist(7, Wish::ReceiveChar('a'));
+        summoner.update_mist(7, Wish::ReceiveChar('b'));
+        let strengths: Vec<f32> = summoner.take_wishes().into_iter().filter_map(|wish| {
+            match wish {
+                Wish::HapticPulse(pulse) => Some(pulse.strength),
+                _ => None,
+            }
+        }).collect();
+        assert_eq!(vec![0.1, 0.2], strengths);
+        assert!(summoner.take_wishes().is_empty());
+    }
+}
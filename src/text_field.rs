@@ -0,0 +1,523 @@
+extern crate cage;
+extern crate rand;
+
+use star::Star;
+use vision::Vision;
+use report::Well;
+use common::Wish;
+use hand::{Hand, HandLock};
+use mist::Mist;
+use patch::{self, Patch, Sigil};
+use theme::{self, Paint};
+use wail::{TextEdit, Motion};
+use cage::Cage;
+
+const CHAR_WIDTH_PER_HEIGHT: f32 = 0.6;
+const LINE_SPACING: f32 = 1.2;
+const CARET_WIDTH: f32 = 0.004;
+const UNFOCUSED_CARET_OPACITY: f32 = 0.3;
+const SELECTION_OPACITY: f32 = 0.4;
+const TEXT_LIFT: f32 = 0.001;
+// Ids of glyph and selection patches count up from their base by character index.
+const MAX_CHARS: u64 = 1 << 20;
+
+// Text with a caret between characters. With an anchor, the characters between anchor and
+// caret are selected. Positions count chars, not bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EditBuffer {
+    chars: Vec<char>,
+    pub caret: usize,
+    pub anchor: Option<usize>,
+}
+
+impl EditBuffer {
+    pub fn new(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let caret = chars.len();
+        EditBuffer { chars: chars, caret: caret, anchor: None }
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        match self.anchor {
+            Some(anchor) if anchor != self.caret => Some((anchor.min(self.caret), anchor.max(self.caret))),
+            _ => None,
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.text().split('\n').map(|line| line.to_string()).collect()
+    }
+
+    // Line and column of a position.
+    pub fn line_col(&self, index: usize) -> (usize, usize) {
+        let (mut line, mut col) = (0, 0);
+        for c in self.chars.iter().take(index) {
+            if *c == '\n' {
+                line += 1;
+                col = 0;
+            } else {
+                col += 1;
+            }
+        }
+        (line, col)
+    }
+
+    // Position at a line and column, clamped to the text.
+    pub fn index_at(&self, line: usize, col: usize) -> usize {
+        let mut index = 0;
+        for (i, text) in self.lines().iter().enumerate() {
+            let line_len = text.chars().count();
+            if i == line {
+                return index + col.min(line_len);
+            }
+            index += line_len + 1;
+        }
+        self.chars.len()
+    }
+
+    pub fn place_caret(&mut self, index: usize, extend: bool) {
+        if extend {
+            if self.anchor.is_none() {
+                self.anchor = Some(self.caret);
+            }
+        } else {
+            self.anchor = None;
+        }
+        self.caret = index.min(self.chars.len());
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        if let Some((start, end)) = self.selection() {
+            self.chars.drain(start..end);
+            self.caret = start;
+            self.anchor = None;
+            true
+        } else {
+            self.anchor = None;
+            false
+        }
+    }
+
+    fn moved(&self, motion: Motion) -> usize {
+        let (line, col) = self.line_col(self.caret);
+        match motion {
+            Motion::Left => if self.caret > 0 { self.caret - 1 } else { 0 },
+            Motion::Right => (self.caret + 1).min(self.chars.len()),
+            Motion::Home => self.index_at(line, 0),
+            Motion::End => self.index_at(line, usize::max_value()),
+            Motion::Up => if line == 0 { 0 } else { self.index_at(line - 1, col) },
+            Motion::Down => if line + 1 >= self.lines().len() { self.chars.len() } else { self.index_at(line + 1, col) },
+        }
+    }
+
+    // Applies an edit, returning whether the text changed. Line breaks are only kept when
+    // multiline; a single line ignores Enter and inserted newlines.
+    pub fn apply(&mut self, edit: TextEdit, multiline: bool) -> bool {
+        match edit {
+            TextEdit::Insert('\n') | TextEdit::Enter => {
+                if multiline {
+                    self.delete_selection();
+                    self.chars.insert(self.caret, '\n');
+                    self.caret += 1;
+                    true
+                } else {
+                    false
+                }
+            },
+            TextEdit::Insert(c) => {
+                self.delete_selection();
+                self.chars.insert(self.caret, c);
+                self.caret += 1;
+                true
+            },
+            TextEdit::Backspace => {
+                if self.delete_selection() {
+                    true
+                } else if self.caret > 0 {
+                    self.caret -= 1;
+                    self.chars.remove(self.caret);
+                    true
+                } else {
+                    false
+                }
+            },
+            TextEdit::Delete => {
+                if self.delete_selection() {
+                    true
+                } else if self.caret < self.chars.len() {
+                    self.chars.remove(self.caret);
+                    true
+                } else {
+                    false
+                }
+            },
+            TextEdit::Move(motion) => {
+                // Moving sideways out of a selection lands on its near edge.
+                let caret = match (self.selection(), motion) {
+                    (Some((start, _)), Motion::Left) => start,
+                    (Some((_, end)), Motion::Right) => end,
+                    _ => self.moved(motion),
+                };
+                self.place_caret(caret, false);
+                false
+            },
+            TextEdit::Select(motion) => {
+                let caret = self.moved(motion);
+                self.place_caret(caret, true);
+                false
+            },
+            TextEdit::SelectAll => {
+                self.anchor = Some(0);
+                self.caret = self.chars.len();
+                false
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextFieldOut {
+    Changed(String),
+    // Enter in a single line field.
+    Submitted(String),
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum TextFieldMsg {
+    Edit(TextEdit),
+    Point(Hand),
+    Focus(bool),
+}
+
+#[derive(Clone, Debug)]
+pub struct TextFieldModel {
+    pub buffer: EditBuffer,
+    pub focused: bool,
+    // The hand pushed into the field, which others cannot interrupt until it pulls out.
+    pointing: HandLock,
+    outs: Vec<TextFieldOut>,
+}
+
+// Pushing a hand into the field past half its depth places the caret under it; moving while
+// pushed in selects from there.
+#[derive(Clone, Debug)]
+pub struct TextField {
+    cage: Cage,
+    text: String,
+    multiline: bool,
+    mist_id: u64,
+    background_id: u64,
+    caret_id: u64,
+    glyph_base_id: u64,
+    selection_base_id: u64,
+}
+
+pub fn text_field(cage: Cage, text: &str) -> TextField {
+    TextField::new(cage, text, false)
+}
+
+pub fn text_area(cage: Cage, text: &str) -> TextField {
+    TextField::new(cage, text, true)
+}
+
+impl TextField {
+    fn new(cage: Cage, text: &str, multiline: bool) -> Self {
+        TextField {
+            cage: cage,
+            text: text.to_string(),
+            multiline: multiline,
+            mist_id: rand::random::<u64>(),
+            background_id: rand::random::<u64>(),
+            caret_id: rand::random::<u64>(),
+            glyph_base_id: rand::random::<u64>() / 2,
+            selection_base_id: rand::random::<u64>() / 2,
+        }
+    }
+
+    fn char_size() -> (f32, f32) {
+        let char_height = theme::current().font_size;
+        (char_height * CHAR_WIDTH_PER_HEIGHT, char_height)
+    }
+
+    fn padding() -> f32 {
+        theme::current().spacing / 2.0
+    }
+
+    // Left and bottom of the character cell at a line and column.
+    fn cell_corner(&self, line: usize, col: usize) -> (f32, f32) {
+        let (left, _, _, top, _, _) = self.cage.limits();
+        let (char_width, char_height) = TextField::char_size();
+        let x = left + TextField::padding() + col as f32 * char_width;
+        let y = top - TextField::padding() - char_height - line as f32 * char_height * LINE_SPACING;
+        (x, y)
+    }
+
+    // The caret position nearest a point on the field.
+    pub fn index_at_point(&self, buffer: &EditBuffer, x: f32, y: f32) -> usize {
+        let (left, _, _, top, _, _) = self.cage.limits();
+        let (char_width, char_height) = TextField::char_size();
+        let line = ((top - TextField::padding() - y) / (char_height * LINE_SPACING)).floor().max(0.0) as usize;
+        let col = ((x - left - TextField::padding()) / char_width).round().max(0.0) as usize;
+        buffer.index_at(line, col)
+    }
+
+    fn text_near(&self) -> f32 {
+        let (_, _, _, _, far, near) = self.cage.limits();
+        (far + near) / 2.0 + TEXT_LIFT
+    }
+
+    fn text_patches(&self, buffer: &EditBuffer) -> Vec<Patch> {
+        let (char_width, char_height) = TextField::char_size();
+        let mut patches = Vec::new();
+        let mut first_index = 0;
+        for (line, text) in buffer.lines().iter().enumerate() {
+            let (x, y) = self.cell_corner(line, 0);
+            patches.extend(patch::text_patches(self.glyph_base_id + first_index as u64 % MAX_CHARS, text, x, y,
+                                               self.text_near(), char_width, char_height, Paint::Text.resolve()));
+            first_index += text.chars().count() + 1;
+        }
+        patches
+    }
+
+    fn selection_patches(&self, buffer: &EditBuffer) -> Vec<Patch> {
+        let (char_width, char_height) = TextField::char_size();
+        let mut patches = Vec::new();
+        if let Some((start, end)) = buffer.selection() {
+            for index in start..end {
+                let (line, col) = buffer.line_col(index);
+                let (x, y) = self.cell_corner(line, col);
+                let patch = Patch::new(self.selection_base_id + index as u64 % MAX_CHARS, x, x + char_width, y, y + char_height,
+                                       self.text_near() - TEXT_LIFT / 2.0, Paint::Accent.resolve(), Sigil::Fill);
+                patches.push(patch.with_opacity(SELECTION_OPACITY));
+            }
+        }
+        patches
+    }
+
+    fn caret_patch(&self, buffer: &EditBuffer, focused: bool) -> Patch {
+        let (_, char_height) = TextField::char_size();
+        let (line, col) = buffer.line_col(buffer.caret);
+        let (x, y) = self.cell_corner(line, col);
+        let patch = Patch::new(self.caret_id, x - CARET_WIDTH / 2.0, x + CARET_WIDTH / 2.0, y, y + char_height,
+                               self.text_near() + TEXT_LIFT, Paint::Accent.resolve(), Sigil::Fill);
+        if focused { patch } else { patch.with_opacity(UNFOCUSED_CARET_OPACITY) }
+    }
+
+    fn is_pushed(&self, hand: &Hand) -> bool {
+        let (x, y, z) = hand.offset.tuple();
+        let (_, _, _, _, far, near) = self.cage.limits();
+        self.cage.contains(x, y, z) && z < (far + near) / 2.0
+    }
+}
+
+impl Star for TextField {
+    type Mdl = TextFieldModel;
+    type Msg = TextFieldMsg;
+    type Out = TextFieldOut;
+
+    fn init(&self) -> TextFieldModel {
+        TextFieldModel { buffer: EditBuffer::new(&self.text), focused: false, pointing: HandLock::default(), outs: Vec::new() }
+    }
+
+    fn view(&self, model: &TextFieldModel) -> Vision<TextFieldMsg> {
+        let mut vision = Vision::new();
+        vision.add_patch(Patch::from_cage(&self.cage, Paint::Surface.resolve(), Sigil::Fill, self.background_id));
+        vision.add_mist(Mist::new(self.mist_id, self.cage).with_focusable(true), |wish| {
+            match wish {
+                Wish::SenseHand(hand) => Some(TextFieldMsg::Point(hand)),
+                Wish::KeyPress(key_press) => TextEdit::from_key_press(key_press).map(TextFieldMsg::Edit),
+                Wish::ReceiveChar(c) => Some(TextFieldMsg::Edit(TextEdit::Insert(c))),
+                Wish::Focus(focused) => Some(TextFieldMsg::Focus(focused)),
+                _ => None,
+            }
+        });
+        let mut text_vision: Vision<TextFieldMsg> = Vision::new();
+        for text_patch in self.text_patches(&model.buffer) {
+            text_vision.add_patch(text_patch);
+        }
+        for selection_patch in self.selection_patches(&model.buffer) {
+            text_vision.add_patch(selection_patch);
+        }
+        text_vision.add_patch(self.caret_patch(&model.buffer, model.focused));
+        vision.add_clipped_vision(text_vision, |x| Some(x), &self.cage);
+        vision
+    }
+
+    fn update(&self, model: &TextFieldModel, message: &TextFieldMsg) -> TextFieldModel {
+        let mut next = TextFieldModel { outs: Vec::new(), ..model.clone() };
+        match message {
+            &TextFieldMsg::Edit(edit) => {
+                if edit == TextEdit::Enter && !self.multiline {
+                    next.outs.push(TextFieldOut::Submitted(next.buffer.text()));
+                } else if next.buffer.apply(edit, self.multiline) {
+                    next.outs.push(TextFieldOut::Changed(next.buffer.text()));
+                }
+            },
+            &TextFieldMsg::Point(hand) => {
+                if model.pointing.admits(&hand) {
+                    let pushed = self.is_pushed(&hand);
+                    if pushed {
+                        let index = self.index_at_point(&next.buffer, hand.offset.x, hand.offset.y);
+                        next.buffer.place_caret(index, model.pointing.is_held());
+                    }
+                    next.pointing = model.pointing.follow(&hand, pushed);
+                }
+            },
+            &TextFieldMsg::Focus(focused) => {
+                next.focused = focused;
+                next.pointing = HandLock::default();
+            },
+        }
+        next
+    }
+
+    fn report<T>(&self, model: &TextFieldModel, well: &mut Well<TextFieldOut, T>) {
+        for out in &model.outs {
+            well.add_out(out.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wail::{TextEdit, Motion};
+    use star::Star;
+    use report::Well;
+    use hand::Hand;
+    use common::{Wish, KeyPress, KeyCode};
+    use cage::{Cage, Offset};
+    use viewer::Viewer;
+    use light::Lighting;
+
+    fn edited(text: &str, caret: usize, edits: Vec<TextEdit>, multiline: bool) -> EditBuffer {
+        let mut buffer = EditBuffer::new(text);
+        buffer.caret = caret;
+        for edit in edits {
+            buffer.apply(edit, multiline);
+        }
+        buffer
+    }
+
+    #[test]
+    fn insert_and_delete_around_the_caret() {
+        let buffer = edited("helo", 3, vec![TextEdit::Insert('l')], false);
+        assert_eq!("hello", buffer.text());
+        assert_eq!(4, buffer.caret);
+        assert_eq!("helo", edited("hello", 3, vec![TextEdit::Backspace], false).text());
+        assert_eq!("helo", edited("hello", 3, vec![TextEdit::Delete], false).text());
+        assert_eq!("hello", edited("hello", 0, vec![TextEdit::Backspace], false).text());
+    }
+
+    #[test]
+    fn typing_replaces_the_selection() {
+        let buffer = edited("hello world", 5, vec![TextEdit::Select(Motion::End), TextEdit::Insert('!')], false);
+        assert_eq!("hello!", buffer.text());
+        assert_eq!(None, buffer.selection());
+        let all = edited("abc", 1, vec![TextEdit::SelectAll], false);
+        assert_eq!(Some((0, 3)), all.selection());
+        assert_eq!("", edited("abc", 1, vec![TextEdit::SelectAll, TextEdit::Backspace], false).text());
+        let collapsed = edited("abc", 0, vec![TextEdit::Select(Motion::Right), TextEdit::Select(Motion::Right),
+                                              TextEdit::Move(Motion::Left)], false);
+        assert_eq!((0, None), (collapsed.caret, collapsed.selection()));
+    }
+
+    #[test]
+    fn enter_breaks_lines_only_when_multiline() {
+        assert_eq!("ab", edited("ab", 1, vec![TextEdit::Enter], false).text());
+        assert_eq!("a\nb", edited("ab", 1, vec![TextEdit::Enter], true).text());
+    }
+
+    #[test]
+    fn up_and_down_keep_the_column() {
+        let buffer = edited("abcd\nef\nghij", 3, vec![TextEdit::Move(Motion::Down)], true);
+        assert_eq!((1, 2), buffer.line_col(buffer.caret));
+        let buffer = edited("abcd\nef\nghij", 11, vec![TextEdit::Move(Motion::Up), TextEdit::Move(Motion::Up)], true);
+        assert_eq!((0, 2), buffer.line_col(buffer.caret));
+        let home = edited("abcd\nef", 7, vec![TextEdit::Move(Motion::Home)], true);
+        assert_eq!(5, home.caret);
+    }
+
+    #[test]
+    fn pushing_a_hand_places_the_caret() {
+        let field = text_field(Cage::from((0.0, 1.0, 0.0, 0.2, -0.1, 0.0)), "hello");
+        let model = field.init();
+        let (x, y) = field.cell_corner(0, 2);
+        let hovering = Hand::at(Offset::from((x, y + 0.01, -0.01)));
+        let model = field.update(&model, &TextFieldMsg::Point(hovering));
+        assert_eq!(5, model.buffer.caret);
+        let pushed = Hand::at(Offset::from((x, y + 0.01, -0.08)));
+        let model = field.update(&model, &TextFieldMsg::Point(pushed));
+        assert_eq!(2, model.buffer.caret);
+        let (x, _) = field.cell_corner(0, 4);
+        let dragged = Hand::at(Offset::from((x, y + 0.01, -0.08)));
+        let model = field.update(&model, &TextFieldMsg::Point(dragged));
+        assert_eq!(Some((2, 4)), model.buffer.selection());
+    }
+
+    #[test]
+    fn edits_report_changes_and_submits() {
+        let field = text_field(Cage::from((0.0, 1.0, 0.0, 0.2, -0.1, 0.0)), "hi");
+        let model = field.update(&field.init(), &TextFieldMsg::Edit(TextEdit::Insert('!')));
+        let model = field.update(&model, &TextFieldMsg::Edit(TextEdit::Enter));
+        let mut well = Well::new(|out| Some(out));
+        field.report(&model, &mut well);
+        assert_eq!(vec![TextFieldOut::Submitted("hi!".to_string())], well.messages);
+        let model = field.update(&model, &TextFieldMsg::Edit(TextEdit::Backspace));
+        let mut well = Well::new(|out| Some(out));
+        field.report(&model, &mut well);
+        assert_eq!(vec![TextFieldOut::Changed("hi".to_string())], well.messages);
+    }
+
+    #[test]
+    fn focused_mist_takes_keys_and_characters() {
+        let field = text_field(Cage::from((0.0, 1.0, 0.0, 0.2, -0.1, 0.0)), "ab");
+        let model = field.init();
+        let vision = field.view(&model);
+        assert!(vision.mists[&field.mist_id].is_focusable());
+        let focus = vision.get_message_option(field.mist_id, Wish::Focus(true)).unwrap();
+        let model = field.update(&model, &focus);
+        assert!(model.focused);
+        let vision = field.view(&model);
+        let left = KeyPress { code: KeyCode::Left, shift: false };
+        let model = field.update(&model, &vision.get_message_option(field.mist_id, Wish::KeyPress(left)).unwrap());
+        let model = field.update(&model, &vision.get_message_option(field.mist_id, Wish::ReceiveChar('x')).unwrap());
+        assert_eq!("axb", model.buffer.text());
+        let tab = KeyPress { code: KeyCode::Tab, shift: false };
+        assert!(vision.get_message_option(field.mist_id, Wish::KeyPress(tab)).is_none());
+    }
+
+    // Shows the model the way app::view does, replacing the viewer's scene with the new vision.
+    fn shown(field: &TextField, model: &TextFieldModel, viewer: &Viewer) -> Vec<u64> {
+        let vision = field.view(model);
+        let lighting = vision.lighting.unwrap_or(Lighting::default());
+        viewer.set_scene(vision.patches, vision.mists, lighting, vision.debug_draws);
+        viewer.get_scene().patches.keys().cloned().collect()
+    }
+
+    #[test]
+    fn deleted_glyphs_and_selections_leave_the_viewer() {
+        let viewer = Viewer::start();
+        let field = TextField { background_id: 1, caret_id: 2, glyph_base_id: 1000, selection_base_id: 2000,
+                                ..text_field(Cage::from((0.0, 1.0, 0.0, 0.2, -0.1, 0.0)), "abc") };
+        let model = field.update(&field.init(), &TextFieldMsg::Edit(TextEdit::SelectAll));
+        let before = shown(&field, &model, &viewer);
+        let model = field.update(&model, &TextFieldMsg::Edit(TextEdit::Move(Motion::End)));
+        let model = field.update(&model, &TextFieldMsg::Edit(TextEdit::Backspace));
+        let after = shown(&field, &model, &viewer);
+        viewer.stop();
+        assert!(before.contains(&(field.glyph_base_id + 2)));
+        assert!(before.contains(&field.selection_base_id));
+        assert!(!after.contains(&(field.glyph_base_id + 2)));
+        assert!(!after.contains(&field.selection_base_id));
+        assert!(after.contains(&(field.glyph_base_id + 1)));
+    }
+}
@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+use color::Color;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub background: [f32; 4],
+    pub surface: [f32; 4],
+    pub accent: [f32; 4],
+    pub text: [f32; 4],
+    pub font_size: f32,
+    pub spacing: f32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            background: [0.05, 0.05, 0.08, 1.0],
+            surface: [0.16, 0.17, 0.21, 1.0],
+            accent: [0.25, 0.6, 1.0, 1.0],
+            text: [0.95, 0.95, 0.95, 1.0],
+            font_size: 0.05,
+            spacing: 0.02,
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            background: [0.94, 0.94, 0.96, 1.0],
+            surface: [1.0, 1.0, 1.0, 1.0],
+            accent: [0.1, 0.45, 0.9, 1.0],
+            text: [0.1, 0.1, 0.12, 1.0],
+            ..Theme::dark()
+        }
+    }
+
+    // Flips between the dark and light presets. Custom themes go back to dark.
+    pub fn toggled(&self) -> Self {
+        if *self == Theme::dark() { Theme::light() } else { Theme::dark() }
+    }
+
+    pub fn clear_color(&self) -> (f32, f32, f32, f32) {
+        (self.background[0], self.background[1], self.background[2], self.background[3])
+    }
+}
+
+// Stars view on the app thread, so the theme they read lives there too. Setting it and
+// viewing again restyles everything that paints with theme colors.
+thread_local!(static CURRENT: RefCell<Theme> = RefCell::new(Theme::default()));
+
+pub fn current() -> Theme {
+    CURRENT.with(|theme| *theme.borrow())
+}
+
+pub fn set_current(theme: Theme) {
+    CURRENT.with(|current| *current.borrow_mut() = theme);
+}
+
+// A color that is either fixed or looked up in the current theme when a vision is built.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Paint {
+    Fixed([f32; 4]),
+    Background,
+    Surface,
+    Accent,
+    Text,
+}
+
+impl From<[f32; 4]> for Paint {
+    fn from(color: [f32; 4]) -> Self {
+        Paint::Fixed(color)
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Paint::Fixed(color.to_array())
+    }
+}
+
+impl Paint {
+    pub fn resolve(&self) -> [f32; 4] {
+        let theme = current();
+        match *self {
+            Paint::Fixed(color) => color,
+            Paint::Background => theme.background,
+            Paint::Surface => theme.surface,
+            Paint::Accent => theme.accent,
+            Paint::Text => theme.text,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use color::RED;
+
+    #[test]
+    fn paint_follows_current_theme() {
+        set_current(Theme::light());
+        assert_eq!(Theme::light().text, Paint::Text.resolve());
+        set_current(Theme::dark());
+        assert_eq!(Theme::dark().text, Paint::Text.resolve());
+        assert_eq!(RED, Paint::from(RED).resolve());
+        assert_eq!(RED, Paint::from(Color::from(RED)).resolve());
+    }
+
+    #[test]
+    fn toggled_switches_presets() {
+        assert_eq!(Theme::light(), Theme::dark().toggled());
+        assert_eq!(Theme::dark(), Theme::light().toggled());
+    }
+}
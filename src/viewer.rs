@@ -4,18 +4,37 @@ use std::collections::HashMap;
 use patch::Patch;
 use mist::Mist;
 use hand::Hand;
+use scene::Scene;
+use light::Lighting;
+use debug::DebugDraw;
+use gaze::Reticle;
+use common::HapticPulse;
+use std::mem;
 
 enum Message {
-    AddPatch(Patch),
+    SetScene(HashMap<u64, Patch>, HashMap<u64, Mist>, Lighting, Vec<DebugDraw>),
     SendPatches(Sender<HashMap<u64, Patch>>),
-    AddMist(Mist),
     SendMists(Sender<HashMap<u64, Mist>>),
     SetHand(Hand),
     SendHand(Sender<Hand>),
-    Clear,
+    SetFocus(Option<u64>),
+    SendFocus(Sender<Option<u64>>),
+    SetReticle(Option<Reticle>),
+    AddHaptic(HapticPulse),
+    TakeHaptics(Sender<Vec<HapticPulse>>),
+    SendScene(Sender<Scene>),
     Stop,
 }
 
+// Hands stay in index order, each replaced by its next position.
+fn set_hand(hands: &mut Vec<Hand>, hand: Hand) {
+    match hands.iter().position(|it| it.index >= hand.index) {
+        Some(position) if hands[position].index == hand.index => hands[position] = hand,
+        Some(position) => hands.insert(position, hand),
+        None => hands.push(hand),
+    }
+}
+
 #[derive(Clone)]
 pub struct Viewer {
     command_tx: Sender<Message>,
@@ -27,16 +46,41 @@ impl Viewer {
         thread::spawn(move || {
             let mut patches = HashMap::new();
             let mut mists = HashMap::new();
-            let mut hand: Hand = Default::default();
+            let mut hands: Vec<Hand> = vec![Default::default()];
+            let mut lighting: Lighting = Default::default();
+            let mut debug_draws = Vec::new();
+            let mut focus = None;
+            let mut reticle = None;
+            let mut haptics = Vec::new();
             while let Ok(message) = rx.recv() {
                 match message {
-                    Message::Clear => { mists.clear(); }
-                    Message::AddPatch(patch) => { patches.insert(patch.id, patch); },
+                    Message::SetScene(next_patches, next_mists, next_lighting, next_debug_draws) => {
+                        patches = next_patches;
+                        mists = next_mists;
+                        lighting = next_lighting;
+                        debug_draws = next_debug_draws;
+                    }
                     Message::SendPatches(tx) => { tx.send(patches.clone()).unwrap(); },
-                    Message::AddMist(mist) => { mists.insert(mist.id(), mist); },
                     Message::SendMists(tx) => { tx.send(mists.clone()).unwrap(); },
-                    Message::SetHand(next_hand) => { hand = next_hand; }
-                    Message::SendHand(tx) => { tx.send(hand.clone()).unwrap(); },
+                    Message::SetHand(next_hand) => { set_hand(&mut hands, next_hand); }
+                    Message::SendHand(tx) => { tx.send(hands[0]).unwrap(); },
+                    Message::SetFocus(next_focus) => { focus = next_focus; }
+                    Message::SendFocus(tx) => { tx.send(focus).unwrap(); },
+                    Message::SetReticle(next_reticle) => { reticle = next_reticle; }
+                    Message::AddHaptic(pulse) => { haptics.push(pulse); }
+                    Message::TakeHaptics(tx) => { tx.send(mem::replace(&mut haptics, Vec::new())).unwrap(); },
+                    Message::SendScene(tx) => {
+                        let scene = Scene {
+                            patches: patches.clone(),
+                            mists: mists.clone(),
+                            hands: hands.clone(),
+                            lighting: lighting.clone(),
+                            debug_draws: debug_draws.clone(),
+                            focus: focus,
+                            reticle: reticle,
+                        };
+                        tx.send(scene).unwrap();
+                    },
                     Message::Stop => { break; }
                 }
             }
@@ -59,10 +103,31 @@ impl Viewer {
         self.command_tx.send(Message::SendHand(tx)).unwrap();
         if let Ok(hand) = rx.recv() { hand } else { Default::default() }
     }
-    pub fn add_patch(&self, patch: Patch) { self.command_tx.send(Message::AddPatch(patch)).unwrap(); }
-    pub fn add_mist(&self, mist: Mist) { self.command_tx.send(Message::AddMist(mist)).unwrap(); }
+    pub fn get_focus(&self) -> Option<u64> {
+        let (tx, rx) = channel();
+        self.command_tx.send(Message::SendFocus(tx)).unwrap();
+        if let Ok(focus) = rx.recv() { focus } else { None }
+    }
+    // Pulses wait here for whichever user thread drives the hands.
+    pub fn take_haptics(&self) -> Vec<HapticPulse> {
+        let (tx, rx) = channel();
+        self.command_tx.send(Message::TakeHaptics(tx)).unwrap();
+        if let Ok(haptics) = rx.recv() { haptics } else { Vec::new() }
+    }
+    pub fn get_scene(&self) -> Scene {
+        let (tx, rx) = channel();
+        self.command_tx.send(Message::SendScene(tx)).unwrap();
+        if let Ok(scene) = rx.recv() { scene } else { Default::default() }
+    }
+    // Replaces everything the stars show in one message so a frame never sees half a scene.
+    pub fn set_scene(&self, patches: HashMap<u64, Patch>, mists: HashMap<u64, Mist>, lighting: Lighting,
+                     debug_draws: Vec<DebugDraw>) {
+        self.command_tx.send(Message::SetScene(patches, mists, lighting, debug_draws)).unwrap();
+    }
     pub fn set_hand(&self, hand: Hand) { self.command_tx.send(Message::SetHand(hand)).unwrap(); }
-    pub fn clear(&self) { self.command_tx.send(Message::Clear).unwrap(); }
+    pub fn set_focus(&self, focus: Option<u64>) { self.command_tx.send(Message::SetFocus(focus)).unwrap(); }
+    pub fn set_reticle(&self, reticle: Option<Reticle>) { self.command_tx.send(Message::SetReticle(reticle)).unwrap(); }
+    pub fn add_haptic(&self, pulse: HapticPulse) { self.command_tx.send(Message::AddHaptic(pulse)).unwrap(); }
     pub fn stop(&self) { self.command_tx.send(Message::Stop).unwrap_or(()); }
 }
 
@@ -70,29 +135,113 @@ impl Viewer {
 mod tests {
     use super::*;
     use patch::{Sigil, Patch};
+    use mist::Mist;
+    use cage::Cage;
     use color::MAGENTA;
 
+    fn patch_map(ids: &[u64]) -> HashMap<u64, Patch> {
+        ids.iter().map(|&id| (id, Patch::new(id, -1.0, 1.0, -1.0, 1.0, 0.0, MAGENTA, Sigil::Fill))).collect()
+    }
+
+    fn mist_map(ids: &[u64]) -> HashMap<u64, Mist> {
+        ids.iter().map(|&id| (id, Mist::new(id, Cage::from((0.0, 0.1, 0.0, 0.1, 0.0, 0.1))))).collect()
+    }
 
     #[test]
-    fn add_patch() {
+    fn set_patches() {
         let viewer = Viewer::start();
-        let patch = Patch::new(1, -1.0, 1.0, -1.0, 1.0, 0.0, MAGENTA, Sigil::Fill);
-        viewer.add_patch(patch);
+        viewer.set_scene(patch_map(&[1]), HashMap::new(), Lighting::default(), Vec::new());
         let report = viewer.get_patches();
         viewer.stop();
         assert!(report.contains_key(&1));
     }
 
     #[test]
-    fn add_mist() {
-        use mist::{Mist};
-        use cage::{Cage};
-
+    fn set_mists() {
         let viewer = Viewer::start();
-        let mist = Mist::new(2, Cage::from((0.0, 0.1, 0.0, 0.1, 0.0, 0.1)));
-        viewer.add_mist(mist);
+        viewer.set_scene(HashMap::new(), mist_map(&[2]), Lighting::default(), Vec::new());
         let report = viewer.get_mists();
         viewer.stop();
         assert!(report.contains_key(&2));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn get_scene() {
+        let viewer = Viewer::start();
+        viewer.set_scene(patch_map(&[1]), mist_map(&[2]), Lighting::default(), Vec::new());
+        let scene = viewer.get_scene();
+        viewer.stop();
+        assert!(scene.patches.contains_key(&1));
+        assert!(scene.mists.contains_key(&2));
+    }
+
+    #[test]
+    fn set_scene_replaces_debug_draws() {
+        use debug::DebugDraw;
+
+        let viewer = Viewer::start();
+        let axes = DebugDraw::Axes { at: [0.0, 0.0, 0.0], size: 0.1 };
+        viewer.set_scene(HashMap::new(), HashMap::new(), Lighting::default(), vec![axes]);
+        assert_eq!(1, viewer.get_scene().debug_draws.len());
+        viewer.set_scene(HashMap::new(), HashMap::new(), Lighting::default(), Vec::new());
+        let scene = viewer.get_scene();
+        viewer.stop();
+        assert!(scene.debug_draws.is_empty());
+    }
+
+    #[test]
+    fn set_scene_removes_patches_not_given_again() {
+        let viewer = Viewer::start();
+        viewer.set_scene(patch_map(&[1, 2]), HashMap::new(), Lighting::default(), Vec::new());
+        viewer.set_scene(patch_map(&[2]), HashMap::new(), Lighting::default(), Vec::new());
+        let scene = viewer.get_scene();
+        viewer.stop();
+        assert!(!scene.patches.contains_key(&1));
+        assert!(scene.patches.contains_key(&2));
+    }
+
+    #[test]
+    fn focus_survives_set_scene() {
+        let viewer = Viewer::start();
+        viewer.set_focus(Some(3));
+        viewer.set_scene(HashMap::new(), HashMap::new(), Lighting::default(), Vec::new());
+        let focus = viewer.get_focus();
+        let scene = viewer.get_scene();
+        viewer.stop();
+        assert_eq!(Some(3), focus);
+        assert_eq!(Some(3), scene.focus);
+    }
+
+    #[test]
+    fn scene_keeps_each_hand_by_index() {
+        use hand::Hand;
+        use cage::Offset;
+
+        let viewer = Viewer::start();
+        viewer.set_hand(Hand::at(Offset::from((0.3, 0.0, 0.0))).with_index(2));
+        viewer.set_hand(Hand::at(Offset::from((0.1, 0.0, 0.0))));
+        viewer.set_hand(Hand::at(Offset::from((0.2, 0.0, 0.0))).with_index(1));
+        let scene = viewer.get_scene();
+        let first = viewer.get_hand();
+        viewer.stop();
+        let xs: Vec<f32> = scene.hands.iter().map(|hand| hand.offset.x).collect();
+        assert_eq!(vec![0.1, 0.2, 0.3], xs);
+        assert_eq!(0.1, first.offset.x);
+    }
+
+    #[test]
+    fn haptics_are_taken_once_and_survive_set_scene() {
+        use common::HapticPulse;
+        use std::time::Duration;
+
+        let viewer = Viewer::start();
+        let pulse = HapticPulse { hand: 1, strength: 0.5, duration: Duration::from_millis(20) };
+        viewer.add_haptic(pulse);
+        viewer.set_scene(HashMap::new(), HashMap::new(), Lighting::default(), Vec::new());
+        let first = viewer.take_haptics();
+        let second = viewer.take_haptics();
+        viewer.stop();
+        assert_eq!(vec![pulse], first);
+        assert!(second.is_empty());
+    }
+}
@@ -5,6 +5,9 @@ use patch::Patch;
 use mist::Mist;
 use beat::Beat;
 use common::Wish;
+use light::Lighting;
+use debug::DebugDraw;
+use cage::Cage;
 
 #[derive(Clone)]
 pub struct Vision<Msg> {
@@ -12,6 +15,8 @@ pub struct Vision<Msg> {
     pub patches: HashMap<u64, Patch>,
     pub mists: HashMap<u64, Mist>,
     pub beats: HashMap<u64, Beat>,
+    pub lighting: Option<Lighting>,
+    pub debug_draws: Vec<DebugDraw>,
 }
 
 impl<Msg> Default for Vision<Msg> {
@@ -27,6 +32,8 @@ impl<Msg> Vision<Msg> {
             patches: HashMap::new(),
             mists: HashMap::new(),
             beats: HashMap::new(),
+            lighting: None,
+            debug_draws: Vec::new(),
         }
     }
     pub fn add_patch(&mut self, patch: Patch) {
@@ -40,6 +47,12 @@ impl<Msg> Vision<Msg> {
         self.adapters.insert(beat.id(), Rc::new(adapter));
         self.beats.insert(beat.id(), beat);
     }
+    pub fn set_lighting(&mut self, lighting: Lighting) {
+        self.lighting = Some(lighting);
+    }
+    pub fn add_debug(&mut self, debug_draw: DebugDraw) {
+        self.debug_draws.push(debug_draw);
+    }
     pub fn add_vision<SubMsg, F>(&mut self, sub_vision: Vision<SubMsg>, adapter: F)
         where SubMsg: 'static, F: Fn(SubMsg) -> Option<Msg> + 'static
     {
@@ -52,6 +65,10 @@ impl<Msg> Vision<Msg> {
         for (id, beat) in sub_vision.beats {
             self.beats.insert(id, beat);
         }
+        if sub_vision.lighting.is_some() {
+            self.lighting = sub_vision.lighting;
+        }
+        self.debug_draws.extend(sub_vision.debug_draws);
         let adapter_rc = Rc::new(adapter);
         for (id, sub_adapter_rc) in sub_vision.adapters {
             let cloned_sub_adapter_rc = sub_adapter_rc.clone();
@@ -65,6 +82,21 @@ impl<Msg> Vision<Msg> {
             self.adapters.insert(id, Rc::new(combined_adapter));
         }
     }
+    // Like add_vision, but patches and mists are trimmed to the clip and dropped when
+    // nothing of them is left inside it.
+    pub fn add_clipped_vision<SubMsg, F>(&mut self, mut sub_vision: Vision<SubMsg>, adapter: F, clip: &Cage)
+        where SubMsg: 'static, F: Fn(SubMsg) -> Option<Msg> + 'static
+    {
+        sub_vision.patches = sub_vision.patches.into_iter()
+                                       .filter_map(|(id, patch)| patch.clipped(clip).map(|clipped| (id, clipped)))
+                                       .collect();
+        sub_vision.mists = sub_vision.mists.into_iter()
+                                     .filter_map(|(id, mist)| mist.clipped(clip).map(|clipped| (id, clipped)))
+                                     .collect();
+        let (mists, beats) = (&sub_vision.mists, &sub_vision.beats);
+        sub_vision.adapters.retain(|id, _| mists.contains_key(id) || beats.contains_key(id));
+        self.add_vision(sub_vision, adapter);
+    }
     pub fn get_message_option(&self, id: u64, wish: Wish) -> Option<Msg> {
         let adapter_rc_op = self.adapters.get(&id);
         if let Some(adapter_rc) = adapter_rc_op {
@@ -124,5 +156,50 @@ mod tests {
         let future = now + Duration::from_millis(10000);
         assert_eq!(0, vision.find_beats(&future).len());
     }
+
+    #[test]
+    fn add_vision_keeps_sub_lighting() {
+        use light::Lighting;
+
+        let mut sub_vision = Vision::new() as Vision<()>;
+        sub_vision.set_lighting(Lighting::unlit());
+        let mut vision = Vision::new() as Vision<()>;
+        vision.add_vision(sub_vision, |_| None);
+        assert_eq!(Some(Lighting::unlit()), vision.lighting);
+    }
+
+    #[test]
+    fn add_vision_keeps_sub_debug_draws() {
+        use debug::DebugDraw;
+        use color::RED;
+
+        let line = DebugDraw::Line { from: [0.0, 0.0, 0.0], to: [0.0, 1.0, 0.0], color: RED };
+        let mut sub_vision = Vision::new() as Vision<()>;
+        sub_vision.add_debug(line.clone());
+        let mut vision = Vision::new() as Vision<()>;
+        vision.add_vision(sub_vision, |_| None);
+        assert_eq!(vec![line], vision.debug_draws);
+    }
+
+    #[test]
+    fn add_clipped_vision_trims_patches_and_mists() {
+        use cage::Cage;
+        use mist::Mist;
+        use patch::{Patch, Sigil};
+        use common::Wish;
+
+        let mut sub_vision = Vision::new() as Vision<u32>;
+        sub_vision.add_patch(Patch::new(1, 0.0, 1.0, 0.0, 1.0, 0.0, [1.0; 4], Sigil::Fill));
+        sub_vision.add_patch(Patch::new(2, 2.0, 3.0, 0.0, 1.0, 0.0, [1.0; 4], Sigil::Fill));
+        sub_vision.add_mist(Mist::new(3, Cage::from((0.0, 1.0, 0.0, 1.0, -0.1, 0.1))), |_| Some(3));
+        sub_vision.add_mist(Mist::new(4, Cage::from((2.0, 3.0, 0.0, 1.0, -0.1, 0.1))), |_| Some(4));
+        let mut vision = Vision::new() as Vision<u32>;
+        vision.add_clipped_vision(sub_vision, |x| Some(x), &Cage::from((0.5, 1.5, 0.0, 1.0, -0.1, 0.1)));
+        assert_eq!(vec![&1], vision.patches.keys().collect::<Vec<&u64>>());
+        assert_eq!(0.5, vision.patches[&1].position.left);
+        assert_eq!(vec![&3], vision.mists.keys().collect::<Vec<&u64>>());
+        assert_eq!(Some(3), vision.get_message_option(3, Wish::Tick));
+        assert_eq!(None, vision.get_message_option(4, Wish::Tick));
+    }
 }
 
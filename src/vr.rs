@@ -52,6 +52,31 @@ impl System {
         }
     }
 
+    // The trigger or the grip, whichever is held.
+    pub fn is_grab_pressed(&self, device_index: usize) -> bool {
+        unsafe {
+            use openvr_sys::{EVRButtonId, TrackedDeviceIndex_t};
+            use std;
+            let system = *{ (&(self.system)).0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            let mut state: openvr_sys::VRControllerState_t = std::mem::zeroed();
+            if !system.GetControllerState.unwrap()(device_index as TrackedDeviceIndex_t, &mut state) {
+                return false;
+            }
+            let trigger = 1u64 << (EVRButtonId::EVRButtonId_k_EButton_SteamVR_Trigger as u64);
+            let grip = 1u64 << (EVRButtonId::EVRButtonId_k_EButton_Grip as u64);
+            (state.ulButtonPressed as u64) & (trigger | grip) != 0
+        }
+    }
+
+    // One pulse lasts at most 3999 microseconds. Longer buzzes repeat it each frame.
+    pub fn trigger_haptic_pulse(&self, device_index: usize, micros: u16) {
+        unsafe {
+            use openvr_sys::TrackedDeviceIndex_t;
+            let system = *{ (&(self.system)).0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            system.TriggerHapticPulse.unwrap()(device_index as TrackedDeviceIndex_t, 0, micros);
+        }
+    }
+
     pub fn up() -> Result<System, Error> {
         let system = try!(openvr::init().map_err(|_| Error::NoSystem));
         let compositor = try!(compositor().map_err(|_| Error::NoCompositor));
@@ -1,8 +1,7 @@
 extern crate glium;
 
 use glium::{DisplayBuild};
-use glium::framebuffer::{SimpleFrameBuffer, ToColorAttachment, ToDepthAttachment};
-use glium::glutin::{Event, ElementState, WindowBuilder};
+use glium::glutin::{WindowBuilder};
 use programs::Programs;
 use programs::SCREEN_TO_WORLD;
 use vr::System;
@@ -11,7 +10,14 @@ use cage::Offset;
 use viewer::Viewer;
 use std::sync::mpsc::Sender;
 use app::{Message as AppMessage};
-use hmd::Hmd;
+use hmd::{Hmd, MirrorMode};
+use cam::Camera;
+use line_program;
+use theme::Theme;
+use gaze::Ray;
+use haptics::HapticSchedule;
+use eyebuffers::EyeBufferSettings;
+use keymap::{Keymap, Key};
 use std::rc::Rc;
 use std::time::{Instant, Duration};
 use std::borrow::Borrow;
@@ -32,13 +38,12 @@ pub fn run(viewer: Viewer, app: Sender<AppMessage>) {
         .with_title("vrcounter").with_depth_buffer(24).build_glium()
         .unwrap();
 
-    let hmd = Hmd::new(&window, &vr);
-    let (mut left_frame, mut right_frame) = (
-        SimpleFrameBuffer::with_depth_buffer(&window, hmd.left_eye.buffers.color.to_color_attachment(),
-                                             hmd.left_eye.buffers.depth.to_depth_attachment()).unwrap(),
-        SimpleFrameBuffer::with_depth_buffer(&window, hmd.right_eye.buffers.color.to_color_attachment(),
-                                             hmd.right_eye.buffers.depth.to_depth_attachment()).unwrap()
-    );
+    let mut hmd = Hmd::new(&window, &vr, EyeBufferSettings::default());
+    let mut keymap = Keymap::init();
+    let mut mirror_mode = MirrorMode::LeftEye;
+    let mut spectator = Camera::spectator();
+    let mut theme = Theme::default();
+    let mut haptics = HapticSchedule::default();
 
     let display = Rc::new(window);
     let mut programs = Programs::new(display.clone(), viewer.clone(), HandType::Vive);
@@ -52,24 +57,69 @@ pub fn run(viewer: Viewer, app: Sender<AppMessage>) {
     'render: loop {
         let poses = vr.await_poses();
         let world_to_hmd = poses.get_world_to_hmd_matrix();
+        let hmd_to_world = poses.get_hmd_to_world_matrix();
 
         let controller_matrix_option = poses.get_controller_to_world_matrix();
         programs.set_controller_model_matrix(&controller_matrix_option);
-        if let Some(matrix) = controller_matrix_option {
+        let mut markers = line_program::axes(&hmd_to_world, 0.15);
+        markers.extend(line_program::pointer(&hmd_to_world, 0.5, [1.0, 1.0, 1.0, 0.6]));
+        app.send(AppMessage::SetGaze(Ray::from_pose(&hmd_to_world))).unwrap_or(());
+        let controllers = poses.get_controllers();
+        for &(_, ref matrix) in &controllers {
+            markers.extend(line_program::axes(matrix, 0.08));
+        }
+        programs.set_markers(markers);
+        programs.update();
+        for (hand_index, &(device_index, matrix)) in controllers.iter().enumerate() {
             let position = (matrix[3][0] - SCREEN_TO_WORLD[3][0],
                             matrix[3][1] - SCREEN_TO_WORLD[3][1],
                             matrix[3][2] - SCREEN_TO_WORLD[3][2]);
-            let hand = Hand { offset: Offset::from(position) };
+            let rotation = [[matrix[0][0], matrix[0][1], matrix[0][2]],
+                            [matrix[1][0], matrix[1][1], matrix[1][2]],
+                            [matrix[2][0], matrix[2][1], matrix[2][2]]];
+            let hand = Hand::at(Offset::from(position)).with_index(hand_index)
+                                                       .with_grab(vr.is_grab_pressed(device_index))
+                                                       .with_rotation(Some(rotation));
             viewer.set_hand(hand);
             app.send(AppMessage::SetHand(hand)).unwrap();
         }
+        let now = Instant::now();
+        for pulse in viewer.take_haptics() {
+            haptics.add(pulse, now);
+        }
+        for (hand_index, micros) in haptics.frame(now) {
+            if let Some(&(device_index, _)) = controllers.get(hand_index) {
+                vr.trigger_haptic_pulse(device_index, micros);
+            }
+        }
 
-        hmd.draw(&programs, &world_to_hmd, display.borrow(), &mut left_frame, &mut right_frame);
+        hmd.draw(&programs, &world_to_hmd, display.borrow(), mirror_mode, &spectator);
 
+        let typing = viewer.get_focus().is_some();
         for ev in display.poll_events() {
-            match ev {
-                glium::glutin::Event::Closed => break 'render,
-                Event::KeyboardInput(ElementState::Pressed, 1, _) => break 'render,
+            let settings = hmd.get_buffer_settings();
+            match keymap.key_for_event(&ev, typing) {
+                Some(Key::Quit) => break 'render,
+                Some(Key::ToggleMists) => programs.toggle_mists(),
+                Some(Key::ToggleDebug) => programs.toggle_debug(),
+                Some(Key::ToggleTheme) => {
+                    theme = theme.toggled();
+                    hmd.set_clear_color(theme.clear_color());
+                    app.send(AppMessage::SetTheme(theme)).unwrap();
+                },
+                Some(Key::CycleMirror) => mirror_mode = mirror_mode.next(),
+                Some(Key::LookUp) => spectator = spectator.move_up(),
+                Some(Key::LookDown) => spectator = spectator.move_down(),
+                Some(Key::LookLeft) => spectator = spectator.move_left(),
+                Some(Key::LookRight) => spectator = spectator.move_right(),
+                Some(Key::LookFar) => spectator = spectator.move_far(),
+                Some(Key::LookNear) => spectator = spectator.move_near(),
+                Some(Key::ResetLook) => spectator = Camera::spectator(),
+                Some(Key::CycleSamples) => hmd.set_buffer_settings(display.borrow(), settings.with_next_samples()),
+                Some(Key::RenderScaleDown) => hmd.set_buffer_settings(display.borrow(), settings.with_scale_step(-1)),
+                Some(Key::RenderScaleUp) => hmd.set_buffer_settings(display.borrow(), settings.with_scale_step(1)),
+                Some(Key::Press(key_press)) => app.send(AppMessage::KeyPress(key_press)).unwrap_or(()),
+                Some(Key::Char(c)) => app.send(AppMessage::ReceiveChar(c)).unwrap_or(()),
                 _ => ()
             }
         }
@@ -0,0 +1,333 @@
+extern crate cage;
+
+use wail::core::{Wail2, Wailing, WailingIn};
+use star::Star;
+use report::Well;
+use vision::Vision;
+use common::Wish;
+use patch::{Patch, PatchPosition};
+use cage::{Frame, Offset, Cage};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Instant;
+use std::mem;
+
+// Runs a wail inside the Star world. Star models are values, but a wailing keeps its own
+// state, so every copy of the model shares the one wailing summoned at init.
+#[derive(Clone)]
+pub struct WailStar<Out> {
+    wail: Wail2<Out>,
+    offset: Offset,
+}
+
+#[derive(Clone)]
+pub struct WailStarModel<Out> {
+    wailing: Rc<RefCell<Box<Wailing<Out>>>>,
+    pub outs: Vec<Out>,
+    pub wishes: Vec<Wish>,
+}
+
+impl<Out> Wail2<Out> where Out: Clone + 'static {
+    pub fn to_star(&self, offset: Offset) -> WailStar<Out> {
+        WailStar { wail: (*self).clone(), offset: offset }
+    }
+}
+
+impl<Out> Star for WailStar<Out> where Out: Clone + 'static {
+    type Mdl = WailStarModel<Out>;
+    type Msg = WailingIn;
+    type Out = Out;
+
+    fn init(&self) -> WailStarModel<Out> {
+        let mut wailing = self.wail.summon();
+        wailing.update(&WailingIn::Offset(self.offset));
+        WailStarModel { wailing: Rc::new(RefCell::new(wailing)), outs: Vec::new(), wishes: Vec::new() }
+    }
+
+    fn view(&self, model: &WailStarModel<Out>) -> Vision<WailingIn> {
+        model.wailing.borrow().view()
+    }
+
+    fn update(&self, model: &WailStarModel<Out>, message: &WailingIn) -> WailStarModel<Out> {
+        let out = model.wailing.borrow_mut().update(message);
+        let wishes = model.wailing.borrow_mut().take_wishes();
+        WailStarModel { wailing: model.wailing.clone(), outs: vec![out], wishes: wishes }
+    }
+
+    fn report<T>(&self, model: &WailStarModel<Out>, well: &mut Well<Out, T>) {
+        for out in &model.outs {
+            well.add_out(out.clone());
+        }
+        for wish in &model.wishes {
+            well.add_wish(*wish);
+        }
+    }
+}
+
+// Runs a star inside a wail. The star draws around the origin and is moved to the wailing's
+// offset. Hands reach every mist in the star's vision and ticks every live beat, as they do for
+// a star run by the app, while gaze selections, keys and focus reach the one mist they name.
+// Whatever the star reports after each message becomes the wailing's output, and its wishes
+// wait for take_wishes.
+pub fn star_wail<S>(star: S, frame: Frame) -> Wail2<Vec<S::Out>> where S: Star + 'static, S::Msg: 'static, S::Out: 'static {
+    Wail2::create(Rc::new(move || {
+        Box::new(StarWailing {
+            star: star.clone(),
+            model: star.init(),
+            frame: frame,
+            offset: Offset::default(),
+            wishes: Vec::new(),
+        }) as Box<Wailing<Vec<S::Out>>>
+    }))
+}
+
+pub struct StarWailing<S: Star> {
+    star: S,
+    model: S::Mdl,
+    frame: Frame,
+    offset: Offset,
+    wishes: Vec<Wish>,
+}
+
+fn shift_cage(cage: &Cage, offset: &Offset) -> Cage {
+    let (left, right, bottom, top, far, near) = cage.limits();
+    Cage::from((left + offset.x, right + offset.x, bottom + offset.y, top + offset.y, far + offset.z, near + offset.z))
+}
+
+fn shift_patch(patch: &Patch, offset: &Offset) -> Patch {
+    let position = patch.position;
+    Patch {
+        position: PatchPosition {
+            left: position.left + offset.x,
+            right: position.right + offset.x,
+            bottom: position.bottom + offset.y,
+            top: position.top + offset.y,
+            near: position.near + offset.z,
+        },
+        ..*patch
+    }
+}
+
+impl<S: Star> Wailing<Vec<S::Out>> for StarWailing<S> where S::Msg: 'static {
+    fn size(&self) -> Frame {
+        self.frame
+    }
+
+    fn view(&self) -> Vision<WailingIn> {
+        let star_vision = self.star.view(&self.model);
+        let mut vision = Vision::new();
+        for (_, patch) in &star_vision.patches {
+            vision.add_patch(shift_patch(patch, &self.offset));
+        }
+        for (_, mist) in &star_vision.mists {
+            let mist_id = mist.id();
+            vision.add_mist(mist.with_cage(shift_cage(mist.cage(), &self.offset)), move |wish| {
+                match wish {
+                    Wish::SenseHand(hand) => Some(WailingIn::Hand(hand)),
+                    Wish::GazeSelect => Some(WailingIn::Select(mist_id)),
+                    Wish::KeyPress(_) | Wish::ReceiveChar(_) | Wish::Focus(_) => Some(WailingIn::FocusWish(mist_id, wish)),
+                    _ => None,
+                }
+            });
+        }
+        for (_, beat) in &star_vision.beats {
+            vision.add_beat(beat.clone(), |wish| {
+                match wish {
+                    Wish::Tick => Some(WailingIn::Tick),
+                    _ => None,
+                }
+            });
+        }
+        vision.debug_draws = star_vision.debug_draws;
+        vision
+    }
+
+    fn update(&mut self, message: &WailingIn) -> Vec<S::Out> {
+        match message {
+            &WailingIn::Offset(offset) => {
+                self.offset = offset;
+                Vec::new()
+            },
+            &WailingIn::Hand(hand) => {
+                let star_vision = self.star.view(&self.model);
+                let mist_ids = star_vision.mists.keys().cloned().collect();
+                self.deliver(&star_vision, mist_ids, Wish::SenseHand(hand.minus_offset(&self.offset)))
+            },
+            &WailingIn::Tick => {
+                let star_vision = self.star.view(&self.model);
+                let beat_ids = star_vision.find_beats(&Instant::now()).iter().map(|beat| beat.id()).collect();
+                self.deliver(&star_vision, beat_ids, Wish::Tick)
+            },
+            &WailingIn::Select(mist_id) => {
+                let star_vision = self.star.view(&self.model);
+                self.deliver(&star_vision, vec![mist_id], Wish::GazeSelect)
+            },
+            &WailingIn::FocusWish(mist_id, wish) => {
+                let star_vision = self.star.view(&self.model);
+                self.deliver(&star_vision, vec![mist_id], wish)
+            },
+        }
+    }
+
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        mem::replace(&mut self.wishes, Vec::new())
+    }
+}
+
+impl<S: Star> StarWailing<S> {
+    fn deliver(&mut self, star_vision: &Vision<S::Msg>, ids: Vec<u64>, wish: Wish) -> Vec<S::Out> {
+        let mut messages = Vec::new();
+        for id in ids {
+            if let Some(message) = star_vision.get_message_option(id, wish.clone()) {
+                messages.push(message);
+            }
+        }
+        let mut outs = Vec::new();
+        for message in &messages {
+            self.model = self.star.update(&self.model, message);
+            let mut well = Well::new(|out| Some(out));
+            self.star.report(&self.model, &mut well);
+            outs.extend(well.messages);
+            self.wishes.extend(well.wishes);
+        }
+        outs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use star::Star;
+    use report::Well;
+    use vision::Vision;
+    use mist::Mist;
+    use common::Wish;
+    use hand::Hand;
+    use cage::{Cage, Frame, Offset};
+    use wail::core::{color_wail, TouchMsg, WailingIn};
+    use color::RED;
+
+    #[derive(Clone)]
+    struct Counter;
+
+    impl Star for Counter {
+        type Mdl = u32;
+        type Msg = ();
+        type Out = u32;
+
+        fn init(&self) -> u32 { 0 }
+
+        fn view(&self, _: &u32) -> Vision<()> {
+            let mut vision = Vision::new();
+            vision.add_mist(Mist::new(9, Cage::from((-0.1, 0.1, -0.1, 0.1, -0.1, 0.1))), |wish| {
+                match wish {
+                    Wish::SenseHand(hand) if hand.offset.x > 0.0 => Some(()),
+                    _ => None,
+                }
+            });
+            vision
+        }
+
+        fn update(&self, count: &u32, _: &()) -> u32 { count + 1 }
+
+        fn report<T>(&self, count: &u32, well: &mut Well<u32, T>) {
+            well.add_out(*count);
+        }
+    }
+
+    #[test]
+    fn wail_star_reports_outputs() {
+        let star = color_wail(RED, Frame::default()).add_touch().to_star(Offset::default());
+        let model = star.init();
+        let hand = WailingIn::Hand(Hand::default());
+        let model = star.update(&model, &hand);
+        let mut well = Well::new(|out| match out {
+            TouchMsg::TouchMove => Some("moved"),
+            TouchMsg::None => None,
+        });
+        star.report(&model, &mut well);
+        assert_eq!(vec!["moved"], well.messages);
+    }
+
+    #[test]
+    fn star_wailing_moves_star_and_hands_by_offset() {
+        let mut wailing = star_wail(Counter, Frame::from((0.2, 0.2, 0.2))).summon();
+        wailing.update(&WailingIn::Offset(Offset::from((-1.0, 0.0, 0.0))));
+        let (left, _, _, _, _, _) = wailing.view().mists[&9].cage().limits();
+        assert!((left + 1.1).abs() < 1e-6);
+        let beside = Hand::at(Offset::from((-1.05, 0.0, 0.0)));
+        assert_eq!(Vec::<u32>::new(), wailing.update(&WailingIn::Hand(beside)));
+        let right_half = Hand::at(Offset::from((-0.95, 0.0, 0.0)));
+        assert_eq!(vec![1], wailing.update(&WailingIn::Hand(right_half)));
+    }
+
+    #[test]
+    fn wrapped_text_fields_take_focus_and_characters() {
+        use text_field::{text_field, TextFieldOut};
+
+        let field = text_field(Cage::from((0.0, 0.4, 0.0, 0.1, -0.05, 0.0)), "a");
+        let mut wailing = star_wail(field, Frame::from((0.4, 0.1, 0.05))).summon();
+        wailing.update(&WailingIn::Offset(Offset::from((1.0, 0.0, 0.0))));
+        let vision = wailing.view();
+        let (&mist_id, mist) = vision.mists.iter().next().unwrap();
+        assert!(mist.is_focusable());
+        let (left, _, _, _, _, _) = mist.cage().limits();
+        assert!((left - 1.0).abs() < 1e-6);
+        let focus = vision.get_message_option(mist_id, Wish::Focus(true)).unwrap();
+        wailing.update(&focus);
+        let typed = vision.get_message_option(mist_id, Wish::ReceiveChar('b')).unwrap();
+        assert_eq!(vec![TextFieldOut::Changed("ab".to_string())], wailing.update(&typed));
+    }
+
+    // Two overlapping mists, each naming itself. The model keeps only the latest message and
+    // every report buzzes the hand.
+    #[derive(Clone)]
+    struct Echo;
+
+    impl Star for Echo {
+        type Mdl = u64;
+        type Msg = u64;
+        type Out = u64;
+
+        fn init(&self) -> u64 { 0 }
+
+        fn view(&self, _: &u64) -> Vision<u64> {
+            let mut vision = Vision::new();
+            for id in 1..3 {
+                vision.add_mist(Mist::new(id, Cage::from((-0.1, 0.1, -0.1, 0.1, -0.1, 0.1))), move |wish| {
+                    match wish {
+                        Wish::SenseHand(_) => Some(id),
+                        _ => None,
+                    }
+                });
+            }
+            vision
+        }
+
+        fn update(&self, _: &u64, message: &u64) -> u64 { *message }
+
+        fn report<T>(&self, last: &u64, well: &mut Well<u64, T>) {
+            use common::HapticPulse;
+            use std::time::Duration;
+            well.add_out(*last);
+            well.add_wish(Wish::HapticPulse(HapticPulse { hand: 0, strength: 0.5, duration: Duration::from_millis(10) }));
+        }
+    }
+
+    #[test]
+    fn each_message_reports_and_wishes_reach_the_wail_star() {
+        let wail = star_wail(Echo, Frame::from((0.2, 0.2, 0.2)));
+        let mut wailing = wail.summon();
+        let mut outs = wailing.update(&WailingIn::Hand(Hand::default()));
+        outs.sort();
+        assert_eq!(vec![1, 2], outs);
+        assert_eq!(2, wailing.take_wishes().len());
+        assert!(wailing.take_wishes().is_empty());
+
+        let star = wail.to_star(Offset::default());
+        let model = star.update(&star.init(), &WailingIn::Hand(Hand::default()));
+        let mut well = Well::new(|_| None as Option<()>);
+        star.report(&model, &mut well);
+        assert_eq!(2, well.wishes.len());
+    }
+}
@@ -0,0 +1,221 @@
+extern crate cage;
+extern crate rand;
+
+use wail::core::{Wail2, Wailing, WailingIn, add_hand_mist};
+use vision::Vision;
+use hand::{Hand, HandLock};
+use cage::{Frame, Offset, Cage};
+use color::Color;
+use theme::{self, Paint};
+use patch::{self, Patch, Sigil};
+use std::rc::Rc;
+
+// Share of the button's depth the hand must push through, from the near face, to press it.
+const PRESS_FRACTION: f32 = 0.5;
+const HOVER_LIGHTEN: f32 = 0.1;
+const LABEL_WIDTH_PER_HEIGHT: f32 = 0.6;
+const LABEL_LIFT: f32 = 0.001;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ButtonMsg {
+    None,
+    Clicked,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ButtonState {
+    Idle,
+    Hovered,
+    Pressed,
+}
+
+pub fn button_wail(label: &str, frame: Frame) -> Wail2<ButtonMsg> {
+    button_wail_with_press_depth(label, frame, default_press_depth(&frame))
+}
+
+pub fn default_press_depth(frame: &Frame) -> f32 {
+    frame.d * PRESS_FRACTION
+}
+
+pub fn button_wail_with_press_depth(label: &str, frame: Frame, press_depth: f32) -> Wail2<ButtonMsg> {
+    let label = label.to_string();
+    Wail2::create(Rc::new(move || {
+        Box::new(ButtonWailing::new(&label, frame, press_depth)) as Box<Wailing<ButtonMsg>>
+    }))
+}
+
+// The hand presses the button once it is inside the button's cage and at least press_depth
+// behind the near face.
+pub fn button_state(cage: &Cage, hand: &Hand, press_depth: f32) -> ButtonState {
+    let (x, y, z) = hand.offset.tuple();
+    if !cage.contains(x, y, z) {
+        ButtonState::Idle
+    } else {
+        let (_, _, _, _, _, near) = cage.limits();
+        if near - z >= press_depth {
+            ButtonState::Pressed
+        } else {
+            ButtonState::Hovered
+        }
+    }
+}
+
+pub struct ButtonWailing {
+    label: String,
+    frame: Frame,
+    offset: Offset,
+    press_depth: f32,
+    state: ButtonState,
+    lock: HandLock,
+    mist_id: u64,
+    patch_id: u64,
+    label_id: u64,
+}
+
+impl ButtonWailing {
+    fn new(label: &str, frame: Frame, press_depth: f32) -> Self {
+        ButtonWailing {
+            label: label.to_string(),
+            frame: frame,
+            offset: Offset::default(),
+            press_depth: press_depth,
+            state: ButtonState::Idle,
+            lock: HandLock::default(),
+            mist_id: rand::random::<u64>(),
+            patch_id: rand::random::<u64>(),
+            label_id: rand::random::<u64>(),
+        }
+    }
+
+    fn cage(&self) -> Cage {
+        Cage::from((self.frame, self.offset))
+    }
+
+    fn face_color(&self) -> [f32; 4] {
+        match self.state {
+            ButtonState::Idle => Paint::Surface.resolve(),
+            ButtonState::Hovered => Color::from(Paint::Surface.resolve()).lighten(HOVER_LIGHTEN).to_array(),
+            ButtonState::Pressed => Paint::Accent.resolve(),
+        }
+    }
+
+    // Label centered on the face, as large as the theme's font allows within the frame.
+    fn label_patches(&self) -> Vec<Patch> {
+        let char_count = self.label.chars().count();
+        if char_count == 0 {
+            return Vec::new();
+        }
+        let (left, right, bottom, top, far, near) = self.cage().limits();
+        let (width, height) = (right - left, top - bottom);
+        let char_height = theme::current().font_size
+                                          .min(height * 0.6)
+                                          .min(width / (char_count as f32 * LABEL_WIDTH_PER_HEIGHT));
+        let char_width = char_height * LABEL_WIDTH_PER_HEIGHT;
+        let label_left = left + (width - char_width * char_count as f32) / 2.0;
+        let label_bottom = bottom + (height - char_height) / 2.0;
+        let label_near = (far + near) / 2.0 + LABEL_LIFT;
+        patch::text_patches(self.label_id, &self.label, label_left, label_bottom, label_near,
+                            char_width, char_height, Paint::Text.resolve())
+    }
+}
+
+impl Wailing<ButtonMsg> for ButtonWailing {
+    fn size(&self) -> Frame {
+        self.frame
+    }
+
+    fn view(&self) -> Vision<WailingIn> {
+        let mut vision = Vision::new();
+        add_hand_mist(&mut vision, self.mist_id, self.cage());
+        vision.add_patch(Patch::from_cage(&self.cage(), self.face_color(), Sigil::Fill, self.patch_id));
+        for label_patch in self.label_patches() {
+            vision.add_patch(label_patch);
+        }
+        vision
+    }
+
+    fn update(&mut self, message: &WailingIn) -> ButtonMsg {
+        match message {
+            &WailingIn::Offset(offset) => {
+                self.offset = offset;
+                ButtonMsg::None
+            },
+            &WailingIn::Hand(hand) => {
+                if !self.lock.admits(&hand) {
+                    return ButtonMsg::None;
+                }
+                let state = button_state(&self.cage(), &hand, self.press_depth);
+                let was_pressed = self.state == ButtonState::Pressed;
+                self.state = state;
+                self.lock = self.lock.follow(&hand, state != ButtonState::Idle);
+                if state == ButtonState::Pressed && !was_pressed {
+                    ButtonMsg::Clicked
+                } else {
+                    ButtonMsg::None
+                }
+            },
+            &WailingIn::Select(mist_id) if mist_id == self.mist_id => ButtonMsg::Clicked,
+            &WailingIn::Tick | &WailingIn::Select(_) | &WailingIn::FocusWish(..) => ButtonMsg::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cage::{Frame, Offset};
+    use hand::Hand;
+    use wail::core::WailingIn;
+    use common::Wish;
+
+    fn hand_at(z: f32) -> WailingIn {
+        WailingIn::Hand(Hand::at(Offset::from((0.0, 0.0, z))))
+    }
+
+    #[test]
+    fn clicks_once_per_press() {
+        let mut button = ButtonWailing::new("OK", Frame::from((0.2, 0.1, 0.1)), 0.05);
+        assert_eq!(ButtonMsg::None, button.update(&hand_at(0.2)));
+        assert_eq!(ButtonMsg::None, button.update(&hand_at(0.04)));
+        assert_eq!(ButtonState::Hovered, button.state);
+        assert_eq!(ButtonMsg::Clicked, button.update(&hand_at(-0.02)));
+        assert_eq!(ButtonMsg::None, button.update(&hand_at(-0.03)));
+        assert_eq!(ButtonMsg::None, button.update(&hand_at(0.04)));
+        assert_eq!(ButtonMsg::Clicked, button.update(&hand_at(-0.02)));
+    }
+
+    #[test]
+    fn label_is_centered_on_the_face() {
+        let button = ButtonWailing::new("AB", Frame::from((0.4, 0.2, 0.1)), 0.05);
+        let labels = button.label_patches();
+        assert_eq!(2, labels.len());
+        let (left, right) = (labels[0].position.left, labels[1].position.right);
+        assert!((left + right).abs() < 1e-6);
+        assert!(labels.iter().all(|label| label.id != button.patch_id));
+    }
+
+    #[test]
+    fn other_hands_wait_until_the_pressing_hand_leaves() {
+        let mut button = ButtonWailing::new("OK", Frame::from((0.2, 0.1, 0.1)), 0.05);
+        let other = |z| WailingIn::Hand(Hand::at(Offset::from((0.0, 0.0, z))).with_index(1));
+        assert_eq!(ButtonMsg::Clicked, button.update(&hand_at(-0.02)));
+        assert_eq!(ButtonMsg::None, button.update(&other(0.5)));
+        assert_eq!(ButtonMsg::None, button.update(&hand_at(-0.02)));
+        assert_eq!(ButtonMsg::None, button.update(&other(0.5)));
+        assert_eq!(ButtonState::Pressed, button.state);
+        assert_eq!(ButtonMsg::None, button.update(&hand_at(0.5)));
+        assert_eq!(ButtonMsg::Clicked, button.update(&other(-0.02)));
+    }
+
+    #[test]
+    fn selecting_its_mist_clicks() {
+        let mut button = ButtonWailing::new("OK", Frame::from((0.2, 0.1, 0.1)), 0.05);
+        let mist_id = button.mist_id;
+        assert_eq!(ButtonMsg::None, button.update(&WailingIn::Select(mist_id + 1)));
+        assert_eq!(ButtonMsg::Clicked, button.update(&WailingIn::Select(mist_id)));
+        match button.view().get_message_option(mist_id, Wish::GazeSelect) {
+            Some(WailingIn::Select(selected_id)) => assert_eq!(mist_id, selected_id),
+            message => panic!("gaze gave {:?}", message),
+        }
+    }
+}
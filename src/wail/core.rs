@@ -5,11 +5,16 @@ use hand::Hand;
 use vision::Vision;
 use cage::{Frame, Offset, Cage};
 use std::rc::Rc;
+use std::time::Instant;
 use std::clone::Clone;
+use theme::Paint;
+use common::Wish;
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum Biopt<A, B> {
     SomeA(A),
     SomeB(B),
+    Both(A, B),
 }
 
 #[derive(Clone)]
@@ -24,20 +29,39 @@ impl<Out> Wail2<Out> where Out: Clone + 'static {
     pub fn summon(&self) -> Box<Wailing<Out>> {
         (*self.on_summon)()
     }
-    pub fn add_touch(&self) -> Wail2<TouchMsg> {
+    pub fn map<B, F>(&self, f: F) -> Wail2<B> where B: Clone + 'static, F: Fn(Out) -> B + 'static {
         let base_wail = (*self).clone();
+        let f_rc: Rc<Fn(Out) -> B> = Rc::new(f);
         Wail2::create(Rc::new(move || {
+            Box::new(MapWailing {
+                base_wailing: base_wail.summon(),
+                f: f_rc.clone(),
+            }) as Box<Wailing<B>>
+        }))
+    }
+    pub fn add_touch(&self) -> Wail2<TouchMsg> {
+        self.add_touch_with(|touch_msg, _| touch_msg)
+    }
+    pub fn add_touch_with<R, F>(&self, f: F) -> Wail2<R> where R: Clone + 'static, F: Fn(TouchMsg, Out) -> R + 'static {
+        let base_wail = (*self).clone();
+        let touch_wail = Wail2::create(Rc::new(move || {
             let base_wailing = base_wail.summon();
             Box::new(TouchWailing {
                 offset: Offset::default(),
                 mist_id: rand::random::<u64>(),
                 base_wailing: base_wailing,
-            }) as Box<Wailing<TouchMsg>>
-        }))
+            }) as Box<Wailing<(TouchMsg, Out)>>
+        }));
+        touch_wail.map(move |(touch_msg, out)| f(touch_msg, out))
     }
     pub fn place_before<ExtOut>(&self, ext_wail: Wail2<ExtOut>) -> Wail2<Out> where ExtOut: Clone + 'static {
+        self.place_before_with(ext_wail, |out, _| out)
+    }
+    pub fn place_before_with<ExtOut, R, F>(&self, ext_wail: Wail2<ExtOut>, f: F) -> Wail2<R>
+        where ExtOut: Clone + 'static, R: Clone + 'static, F: Fn(Out, ExtOut) -> R + 'static
+    {
         let base_wail = (*self).clone();
-        Wail2::create(Rc::new(move || {
+        let pair_wail = Wail2::create(Rc::new(move || {
             let ext_wailing = ext_wail.summon();
             let base_offset = Offset::from((0.0, 0.0, 0.10));
             let mut base_wailing = base_wail.summon();
@@ -46,12 +70,118 @@ impl<Out> Wail2<Out> where Out: Clone + 'static {
                 base_offset: base_offset,
                 base_wailing: base_wailing,
                 ext_wailing: ext_wailing,
-            }) as Box<Wailing<Out>>
+            }) as Box<Wailing<(Out, ExtOut)>>
+        }));
+        pair_wail.map(move |(out, ext_out)| f(out, ext_out))
+    }
+}
+
+impl<A> Wail2<Option<A>> where A: Clone + 'static {
+    // Passes on only the outputs that f keeps, turning each into f's value.
+    pub fn filter_map<B, F>(&self, f: F) -> Wail2<Option<B>> where B: Clone + 'static, F: Fn(A) -> Option<B> + 'static {
+        self.map(move |a_option| a_option.and_then(|a| f(a)))
+    }
+    // Reports whichever of the two had something to say. Both wailings keep their own
+    // layout; each sees every message either one asks for.
+    pub fn either<B>(&self, ext_wail: Wail2<Option<B>>) -> Wail2<Option<Biopt<A, B>>> where B: Clone + 'static {
+        let base_wail = (*self).clone();
+        Wail2::create(Rc::new(move || {
+            Box::new(EitherWailing {
+                a_wailing: base_wail.summon(),
+                b_wailing: ext_wail.summon(),
+            }) as Box<Wailing<Option<Biopt<A, B>>>>
         }))
     }
 }
 
-pub fn color_wail(color: [f32; 4], frame: Frame) -> Wail2<()> {
+pub struct EitherWailing<A, B> {
+    a_wailing: Box<Wailing<Option<A>>>,
+    b_wailing: Box<Wailing<Option<B>>>,
+}
+
+impl<A, B> Wailing<Option<Biopt<A, B>>> for EitherWailing<A, B> {
+    fn size(&self) -> Frame {
+        let a_frame = (*self.a_wailing).size();
+        let b_frame = (*self.b_wailing).size();
+        Frame::from((a_frame.w.max(b_frame.w), a_frame.h.max(b_frame.h), a_frame.d.max(b_frame.d)))
+    }
+
+    fn view(&self) -> Vision<WailingIn> {
+        let mut vision = Vision::new();
+        vision.add_vision((*self.a_wailing).view(), |x| Some(x));
+        vision.add_vision((*self.b_wailing).view(), |x| Some(x));
+        vision
+    }
+
+    fn update(&mut self, message: &WailingIn) -> Option<Biopt<A, B>> {
+        let a_option = (*self.a_wailing).update(message);
+        let b_option = (*self.b_wailing).update(message);
+        match (a_option, b_option) {
+            (Some(a), Some(b)) => Some(Biopt::Both(a, b)),
+            (Some(a), None) => Some(Biopt::SomeA(a)),
+            (None, Some(b)) => Some(Biopt::SomeB(b)),
+            (None, None) => None,
+        }
+    }
+
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        let mut wishes = (*self.a_wailing).take_wishes();
+        wishes.extend((*self.b_wailing).take_wishes());
+        wishes
+    }
+}
+
+pub struct MapWailing<A, B> {
+    base_wailing: Box<Wailing<A>>,
+    f: Rc<Fn(A) -> B>,
+}
+
+impl<A, B> Wailing<B> for MapWailing<A, B> {
+    fn size(&self) -> Frame {
+        (*self.base_wailing).size()
+    }
+
+    fn view(&self) -> Vision<WailingIn> {
+        (*self.base_wailing).view()
+    }
+
+    fn update(&mut self, message: &WailingIn) -> B {
+        (*self.f)((*self.base_wailing).update(message))
+    }
+
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        (*self.base_wailing).take_wishes()
+    }
+}
+
+// A mist over the cage that passes sensed hands on as WailingIn::Hand and a gaze dwelling
+// on it as WailingIn::Select.
+pub fn add_hand_mist(vision: &mut Vision<WailingIn>, mist_id: u64, cage: Cage) {
+    use mist::Mist;
+    use common::Wish;
+    vision.add_mist(Mist::new(mist_id, cage), move |wish| {
+        match wish {
+            Wish::SenseHand(hand) => Some(WailingIn::Hand(hand)),
+            Wish::GazeSelect => Some(WailingIn::Select(mist_id)),
+            _ => None,
+        }
+    });
+}
+
+// A beat lasting until the given instant that asks for WailingIn::Tick on each animation frame.
+pub fn add_tick_beat(vision: &mut Vision<WailingIn>, beat_id: u64, until: Instant) {
+    use beat::Beat;
+    use common::Wish;
+    vision.add_beat(Beat::until_instant(beat_id, until), |wish| {
+        match wish {
+            Wish::Tick => Some(WailingIn::Tick),
+            _ => None,
+        }
+    });
+}
+
+pub fn color_wail<C>(color: C, frame: Frame) -> Wail2<()> where C: Into<Paint> {
+    let color = color.into();
     Wail2::create(Rc::new(move || {
         Box::new(ColorWailing {
             frame: frame, offset: Offset::default(),
@@ -66,7 +196,7 @@ pub struct BaseBeforeExtWailing<BaseOut, ExtOut> {
     ext_wailing: Box<Wailing<ExtOut>>,
 }
 
-impl<BaseOut, ExtOut> Wailing<BaseOut> for BaseBeforeExtWailing<BaseOut, ExtOut> {
+impl<BaseOut, ExtOut> Wailing<(BaseOut, ExtOut)> for BaseBeforeExtWailing<BaseOut, ExtOut> {
     fn size(&self) -> Frame {
         let base_frame = (*self.base_wailing).size();
         let ext_frame = (*self.ext_wailing).size();
@@ -84,17 +214,24 @@ impl<BaseOut, ExtOut> Wailing<BaseOut> for BaseBeforeExtWailing<BaseOut, ExtOut>
         vision
     }
 
-    fn update(&mut self, message: &WailingIn) -> BaseOut {
+    fn update(&mut self, message: &WailingIn) -> (BaseOut, ExtOut) {
         if let &WailingIn::Offset(offset) = message {
-            (*self.ext_wailing).update(&WailingIn::Offset(offset.clone()));
-            (*self.base_wailing).update(&WailingIn::Offset(offset.shift(self.base_offset.x,
-                                                                        self.base_offset.y,
-                                                                        self.base_offset.z)))
+            let ext_out = (*self.ext_wailing).update(&WailingIn::Offset(offset.clone()));
+            let base_out = (*self.base_wailing).update(&WailingIn::Offset(offset.shift(self.base_offset.x,
+                                                                                        self.base_offset.y,
+                                                                                        self.base_offset.z)));
+            (base_out, ext_out)
         } else {
-            (*self.ext_wailing).update(message);
-            (*self.base_wailing).update(message)
+            let ext_out = (*self.ext_wailing).update(message);
+            (((*self.base_wailing).update(message)), ext_out)
         }
     }
+
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        let mut wishes = (*self.base_wailing).take_wishes();
+        wishes.extend((*self.ext_wailing).take_wishes());
+        wishes
+    }
 }
 
 pub struct TouchWailing<BaseOut> {
@@ -103,44 +240,49 @@ pub struct TouchWailing<BaseOut> {
     base_wailing: Box<Wailing<BaseOut>>,
 }
 
-impl<BaseOut> Wailing<TouchMsg> for TouchWailing<BaseOut> {
+impl<BaseOut> Wailing<(TouchMsg, BaseOut)> for TouchWailing<BaseOut> {
     fn size(&self) -> Frame {
         (*self.base_wailing).size()
     }
 
     fn view(&self) -> Vision<WailingIn> {
-        use mist::Mist;
-        use common::Wish;
         let mut vision = Vision::new();
-        vision.add_mist(Mist::new(self.mist_id, Cage::from((self.size(), self.offset))), |wish| {
-            match wish {
-                Wish::SenseHand(hand) => Some(WailingIn::Hand(hand)),
+        add_hand_mist(&mut vision, self.mist_id, Cage::from((self.size(), self.offset)));
+        let base_vision = (*self.base_wailing).view();
+        vision.add_vision(base_vision, |wailing_in| {
+            match wailing_in {
+                WailingIn::Tick => Some(WailingIn::Tick),
                 _ => None,
             }
         });
-        let base_vision = (*self.base_wailing).view();
-        vision.add_vision(base_vision, |_| { None });
         vision
     }
 
-    fn update(&mut self, message: &WailingIn) -> TouchMsg {
+    // The base's own mists are hidden behind the touch mist, so it sees hands through this update.
+    fn update(&mut self, message: &WailingIn) -> (TouchMsg, BaseOut) {
         match message {
             &WailingIn::Offset(offset) => {
                 self.offset = offset;
-                (*self.base_wailing).update(&WailingIn::Offset(offset));
-                TouchMsg::None
+                (TouchMsg::None, (*self.base_wailing).update(&WailingIn::Offset(offset)))
             },
             &WailingIn::Hand(_) => {
-                TouchMsg::TouchMove
+                (TouchMsg::TouchMove, (*self.base_wailing).update(message))
+            },
+            &WailingIn::Tick | &WailingIn::Select(_) | &WailingIn::FocusWish(..) => {
+                (TouchMsg::None, (*self.base_wailing).update(message))
             },
         }
     }
+
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        (*self.base_wailing).take_wishes()
+    }
 }
 
 pub struct ColorWailing {
     frame: Frame,
     offset: Offset,
-    color: [f32; 4],
+    color: Paint,
     patch_id: u64,
 }
 
@@ -153,14 +295,14 @@ impl Wailing<()> for ColorWailing {
         use patch::{Patch, Sigil};
         use cage::Cage;
         let mut vision = Vision::new();
-        let patch = Patch::from_cage(&Cage::from((self.frame, self.offset)), self.color, Sigil::Fill, self.patch_id);
+        let patch = Patch::from_cage(&Cage::from((self.frame, self.offset)), self.color.resolve(), Sigil::Fill, self.patch_id);
         vision.add_patch(patch);
         vision
     }
 
     fn update(&mut self, message: &WailingIn) -> () {
         match message {
-            &WailingIn::Hand(_) => (),
+            &WailingIn::Hand(_) | &WailingIn::Tick | &WailingIn::Select(_) | &WailingIn::FocusWish(..) => (),
             &WailingIn::Offset(offset) => {
                 self.offset = offset;
             }
@@ -173,9 +315,14 @@ pub trait Wailing<MsgOut> {
     fn size(&self) -> Frame;
     fn view(&self) -> Vision<WailingIn>;
     fn update(&mut self, message: &WailingIn) -> MsgOut;
+    // Wishes for the app, like haptic pulses, raised since the last call. Wailings holding
+    // others pass their children's wishes up.
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        Vec::new()
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum TouchMsg {
     None,
     TouchMove
@@ -185,4 +332,68 @@ pub enum TouchMsg {
 pub enum WailingIn {
     Offset(Offset),
     Hand(Hand),
+    Tick,
+    // A gaze dwelled on the mist with this id. Controls owning that mist take it as a click.
+    Select(u64),
+    // A KeyPress, ReceiveChar or Focus wish for the focusable mist with this id.
+    FocusWish(u64, Wish),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cage::{Frame, Offset};
+    use hand::Hand;
+    use color::RED;
+
+    fn hand_in() -> WailingIn {
+        WailingIn::Hand(Hand::at(Offset::from((0.0, 0.0, 0.0))))
+    }
+
+    #[test]
+    fn map_transforms_output() {
+        let mut wailing = color_wail(RED, Frame::default()).map(|_| 5).summon();
+        assert_eq!(5, wailing.update(&hand_in()));
+    }
+
+    #[test]
+    fn filter_map_keeps_some() {
+        let wail = color_wail(RED, Frame::default()).add_touch().map(|touch_msg| match touch_msg {
+            TouchMsg::TouchMove => Some(4),
+            TouchMsg::None => None,
+        });
+        let mut halves = wail.filter_map(|n| if n % 2 == 0 { Some(n / 2) } else { None }).summon();
+        assert_eq!(None, halves.update(&WailingIn::Offset(Offset::default())));
+        assert_eq!(Some(2), halves.update(&hand_in()));
+        let mut odds = wail.filter_map(|n| if n % 2 == 1 { Some(n) } else { None }).summon();
+        assert_eq!(None, odds.update(&hand_in()));
+    }
+
+    #[test]
+    fn either_reports_both_sides() {
+        let a = color_wail(RED, Frame::default()).map(|_| Some(1));
+        let b = color_wail(RED, Frame::default()).map(|_| None as Option<&str>);
+        assert_eq!(Some(Biopt::SomeA(1)), a.either(b).summon().update(&hand_in()));
+        let c = color_wail(RED, Frame::default()).map(|_| Some("c"));
+        assert_eq!(Some(Biopt::Both(1, "c")), a.either(c).summon().update(&hand_in()));
+    }
+
+    #[test]
+    fn either_leaves_layout_alone() {
+        let a = color_wail(RED, Frame::default()).map(|_| Some(1));
+        let b = color_wail(RED, Frame::default()).map(|_| Some(2));
+        let mut wailing = a.either(b).summon();
+        wailing.update(&WailingIn::Offset(Offset::from((0.0, 0.0, 0.5))));
+        let vision = wailing.view();
+        assert_eq!(2, vision.patches.len());
+        assert!(vision.patches.values().all(|patch| patch.position.near == 0.5));
+    }
+
+    #[test]
+    fn add_touch_with_keeps_base_output() {
+        let base = color_wail(RED, Frame::default()).map(|_| 7);
+        let mut wailing = base.add_touch_with(|touch_msg, out| (touch_msg, out)).summon();
+        let (touch_msg, out) = wailing.update(&hand_in());
+        assert_eq!((TouchMsg::TouchMove, 7), (touch_msg, out));
+    }
 }
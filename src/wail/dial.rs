@@ -0,0 +1,163 @@
+extern crate cage;
+extern crate rand;
+
+use wail::core::{Wail2, Wailing, WailingIn, add_hand_mist};
+use wail::button::default_press_depth;
+use wail::slider::{ValueMsg, ValueRange, thumb_color, change_value, engage};
+use vision::Vision;
+use hand::HandLock;
+use cage::{Frame, Offset, Cage};
+use theme::Paint;
+use patch::{Patch, Sigil};
+use std::rc::Rc;
+
+// The dial sweeps clockwise from the lower left to the lower right, leaving a gap at the bottom.
+const START_DEGREES: f32 = 225.0;
+const SWEEP_DEGREES: f32 = 270.0;
+const THUMB_SIZE: f32 = 0.16;
+const THUMB_RADIUS: f32 = 0.36;
+const THUMB_LIFT: f32 = 0.002;
+
+pub fn dial_wail(frame: Frame, range: ValueRange, value: f32) -> Wail2<ValueMsg> {
+    Wail2::create(Rc::new(move || {
+        Box::new(DialWailing::new(frame, range, value)) as Box<Wailing<ValueMsg>>
+    }))
+}
+
+// Fraction of the sweep the point sits at, seen from the center. Points in the gap snap to
+// whichever end is closer.
+pub fn dial_fraction(center_x: f32, center_y: f32, x: f32, y: f32) -> f32 {
+    let degrees = (y - center_y).atan2(x - center_x).to_degrees();
+    let clockwise = ((START_DEGREES - degrees) % 360.0 + 360.0) % 360.0;
+    if clockwise <= SWEEP_DEGREES {
+        clockwise / SWEEP_DEGREES
+    } else if clockwise < (SWEEP_DEGREES + 360.0) / 2.0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+pub fn dial_degrees(fraction: f32) -> f32 {
+    START_DEGREES - fraction * SWEEP_DEGREES
+}
+
+pub struct DialWailing {
+    frame: Frame,
+    offset: Offset,
+    range: ValueRange,
+    value: f32,
+    engaged: bool,
+    lock: HandLock,
+    mist_id: u64,
+    face_id: u64,
+    thumb_id: u64,
+}
+
+impl DialWailing {
+    fn new(frame: Frame, range: ValueRange, value: f32) -> Self {
+        DialWailing {
+            frame: frame,
+            offset: Offset::default(),
+            range: range,
+            value: range.clamp(value),
+            engaged: false,
+            lock: HandLock::default(),
+            mist_id: rand::random::<u64>(),
+            face_id: rand::random::<u64>(),
+            thumb_id: rand::random::<u64>(),
+        }
+    }
+
+    fn cage(&self) -> Cage {
+        Cage::from((self.frame, self.offset))
+    }
+
+    fn thumb_cage(&self) -> Cage {
+        let (left, right, bottom, top, far, near) = self.cage().limits();
+        let (width, height) = (right - left, top - bottom);
+        let radians = dial_degrees(self.range.fraction_of(self.value)).to_radians();
+        let center_x = (left + right) / 2.0 + radians.cos() * width * THUMB_RADIUS;
+        let center_y = (bottom + top) / 2.0 + radians.sin() * height * THUMB_RADIUS;
+        let (half_width, half_height) = (width * THUMB_SIZE / 2.0, height * THUMB_SIZE / 2.0);
+        Cage::from((center_x - half_width, center_x + half_width, center_y - half_height, center_y + half_height,
+                    far + THUMB_LIFT, near + THUMB_LIFT))
+    }
+}
+
+impl Wailing<ValueMsg> for DialWailing {
+    fn size(&self) -> Frame {
+        self.frame
+    }
+
+    fn view(&self) -> Vision<WailingIn> {
+        let mut vision = Vision::new();
+        add_hand_mist(&mut vision, self.mist_id, self.cage());
+        vision.add_patch(Patch::from_cage(&self.cage(), Paint::Surface.resolve(), Sigil::Fill, self.face_id));
+        vision.add_patch(Patch::from_cage(&self.thumb_cage(), thumb_color(self.engaged), Sigil::Fill, self.thumb_id));
+        vision
+    }
+
+    fn update(&mut self, message: &WailingIn) -> ValueMsg {
+        match message {
+            &WailingIn::Offset(offset) => {
+                self.offset = offset;
+                ValueMsg::None
+            },
+            &WailingIn::Hand(hand) => {
+                let cage = self.cage();
+                match engage(&mut self.lock, &mut self.engaged, &hand, &cage, default_press_depth(&self.frame)) {
+                    Some(hand) => {
+                        let (left, right, bottom, top, _, _) = cage.limits();
+                        let (center_x, center_y) = ((left + right) / 2.0, (bottom + top) / 2.0);
+                        let fraction = dial_fraction(center_x, center_y, hand.offset.x, hand.offset.y);
+                        let next_value = self.range.value_at(fraction);
+                        change_value(&mut self.value, next_value)
+                    },
+                    None => ValueMsg::None,
+                }
+            },
+            &WailingIn::Select(mist_id) if mist_id == self.mist_id => {
+                let next_value = self.range.stepped(self.value);
+                change_value(&mut self.value, next_value)
+            },
+            &WailingIn::Tick | &WailingIn::Select(_) | &WailingIn::FocusWish(..) => ValueMsg::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cage::{Frame, Offset};
+    use hand::Hand;
+    use wail::core::WailingIn;
+    use wail::slider::{ValueMsg, ValueRange};
+
+    fn close(expected: f32, actual: f32) -> bool {
+        (expected - actual).abs() < 1e-4
+    }
+
+    #[test]
+    fn angle_maps_clockwise_from_lower_left() {
+        assert!(close(0.0, dial_fraction(0.0, 0.0, -1.0, -1.0)));
+        assert!(close(1.0 / 6.0, dial_fraction(0.0, 0.0, -1.0, 0.0)));
+        assert!(close(0.5, dial_fraction(0.0, 0.0, 0.0, 1.0)));
+        assert!(close(1.0, dial_fraction(0.0, 0.0, 1.0, -1.0)));
+    }
+
+    #[test]
+    fn gap_snaps_to_nearest_end() {
+        assert_eq!(1.0, dial_fraction(0.0, 0.0, 0.1, -1.0));
+        assert_eq!(0.0, dial_fraction(0.0, 0.0, -0.1, -1.0));
+    }
+
+    #[test]
+    fn engaged_hand_turns_the_dial() {
+        let mut dial = DialWailing::new(Frame::from((0.2, 0.2, 0.1)), ValueRange::new(0.0, 270.0).with_step(1.0), 0.0);
+        let pressing_top = Hand::at(Offset::from((0.0, 0.09, -0.01)));
+        assert_eq!(ValueMsg::ValueChanged(135.0), dial.update(&WailingIn::Hand(pressing_top)));
+        let hovering_right = Hand::at(Offset::from((0.09, 0.0, 0.04)));
+        assert_eq!(ValueMsg::None, dial.update(&WailingIn::Hand(hovering_right)));
+    }
+}
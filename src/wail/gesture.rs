@@ -0,0 +1,359 @@
+extern crate cage;
+extern crate rand;
+
+use wail::core::{Wail2, Wailing, WailingIn, add_hand_mist, add_tick_beat};
+use common::Wish;
+use vision::Vision;
+use hand::Hand;
+use mist_program::{mist_state, MistState};
+use cage::{Frame, Offset, Cage};
+use std::collections::BTreeMap;
+use std::f32::consts::PI;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+// Distances are in the x-y plane of the cage, in world units.
+const TAP_MILLIS: u64 = 300;
+const TAP_SLOP: f32 = 0.02;
+const DOUBLE_TAP_MILLIS: u64 = 500;
+const SWIPE_MILLIS: u64 = 300;
+const SWIPE_DISTANCE: f32 = 0.12;
+// The swipe's main axis must be this many times longer than the other.
+const SWIPE_STRAIGHTNESS: f32 = 2.0;
+const DWELL_MILLIS: u64 = 800;
+const DWELL_SLOP: f32 = 0.02;
+const CIRCLE_MILLIS: u64 = 2000;
+const CIRCLE_TURN: f32 = 1.9 * PI;
+const CIRCLE_MIN_RADIUS: f32 = 0.03;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+// A double tap also reports its first tap as a Tap when it happens.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Gesture {
+    Tap,
+    DoubleTap,
+    Swipe(SwipeDirection),
+    Dwell,
+    Circle { clockwise: bool },
+}
+
+#[derive(Clone, Debug, Default)]
+struct Track {
+    // The path while inside the cage, no older than the longest gesture.
+    path: Vec<(Offset, Instant)>,
+    press: Option<(Offset, Instant)>,
+    last_tap: Option<(Offset, Instant)>,
+    rest: Option<(Offset, Instant)>,
+    dwelled: bool,
+}
+
+fn planar_distance(a: &Offset, b: &Offset) -> f32 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    (dx * dx + dy * dy).sqrt()
+}
+
+fn within(earlier: Instant, later: Instant, millis: u64) -> bool {
+    later.duration_since(earlier) <= Duration::from_millis(millis)
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    if angle > PI {
+        angle - 2.0 * PI
+    } else if angle < -PI {
+        angle + 2.0 * PI
+    } else {
+        angle
+    }
+}
+
+fn swipe_direction(from: &Offset, to: &Offset) -> Option<SwipeDirection> {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    if dx.abs() >= SWIPE_DISTANCE && dx.abs() >= dy.abs() * SWIPE_STRAIGHTNESS {
+        Some(if dx > 0.0 { SwipeDirection::Right } else { SwipeDirection::Left })
+    } else if dy.abs() >= SWIPE_DISTANCE && dy.abs() >= dx.abs() * SWIPE_STRAIGHTNESS {
+        Some(if dy > 0.0 { SwipeDirection::Up } else { SwipeDirection::Down })
+    } else {
+        None
+    }
+}
+
+// Sums the turns of the path around its centroid. Counter-clockwise turns are positive.
+fn circle_turn(path: &Vec<(Offset, Instant)>) -> Option<f32> {
+    if path.len() < 3 {
+        return None;
+    }
+    let count = path.len() as f32;
+    let center_x = path.iter().map(|&(offset, _)| offset.x).sum::<f32>() / count;
+    let center_y = path.iter().map(|&(offset, _)| offset.y).sum::<f32>() / count;
+    let center = Offset::from((center_x, center_y, 0.0));
+    let mean_radius = path.iter().map(|&(offset, _)| planar_distance(&center, &offset)).sum::<f32>() / count;
+    if mean_radius < CIRCLE_MIN_RADIUS {
+        return None;
+    }
+    let angles: Vec<f32> = path.iter().map(|&(offset, _)| (offset.y - center_y).atan2(offset.x - center_x)).collect();
+    Some(angles.windows(2).map(|pair| wrap_angle(pair[1] - pair[0])).sum())
+}
+
+impl Track {
+    fn sense(&mut self, cage: &Cage, hand: &Hand, instant: Instant) -> Vec<Gesture> {
+        let mut gestures = Vec::new();
+        let offset = hand.offset;
+        let state = mist_state(cage, hand);
+        if state == MistState::Pressed {
+            if self.press.is_none() {
+                self.press = Some((offset, instant));
+            }
+        } else if let Some((press_offset, press_instant)) = self.press.take() {
+            if within(press_instant, instant, TAP_MILLIS) && planar_distance(&press_offset, &offset) <= TAP_SLOP {
+                match self.last_tap {
+                    Some((tap_offset, tap_instant)) if within(tap_instant, instant, DOUBLE_TAP_MILLIS)
+                        && planar_distance(&tap_offset, &offset) <= TAP_SLOP => {
+                        gestures.push(Gesture::DoubleTap);
+                        self.last_tap = None;
+                    },
+                    _ => {
+                        gestures.push(Gesture::Tap);
+                        self.last_tap = Some((offset, instant));
+                    },
+                }
+            }
+        }
+
+        if state == MistState::Idle {
+            self.path.clear();
+            self.rest = None;
+            self.dwelled = false;
+            return gestures;
+        }
+
+        self.path.retain(|&(_, earlier)| within(earlier, instant, CIRCLE_MILLIS));
+        self.path.push((offset, instant));
+        let swipe = self.path.iter()
+                             .find(|&&(_, earlier)| within(earlier, instant, SWIPE_MILLIS))
+                             .and_then(|&(start, _)| swipe_direction(&start, &offset));
+        if let Some(direction) = swipe {
+            gestures.push(Gesture::Swipe(direction));
+            self.path.clear();
+        } else if let Some(turn) = circle_turn(&self.path) {
+            if turn.abs() >= CIRCLE_TURN {
+                gestures.push(Gesture::Circle { clockwise: turn < 0.0 });
+                self.path.clear();
+            }
+        }
+
+        match self.rest {
+            Some((rest_offset, rest_instant)) if planar_distance(&rest_offset, &offset) <= DWELL_SLOP => {
+                if !self.dwelled && instant.duration_since(rest_instant) >= Duration::from_millis(DWELL_MILLIS) {
+                    gestures.push(Gesture::Dwell);
+                    self.dwelled = true;
+                }
+            },
+            _ => {
+                self.rest = Some((offset, instant));
+                self.dwelled = false;
+            },
+        }
+        gestures
+    }
+}
+
+// Recognizes gestures of each hand inside a cage from its timed samples. Like Stretch, it is a
+// plain value so stars can keep it in their model.
+#[derive(Clone, Debug, Default)]
+pub struct Gestures {
+    tracks: BTreeMap<usize, Track>,
+}
+
+impl Gestures {
+    pub fn sense(&self, cage: &Cage, hand: &Hand, instant: Instant) -> (Gestures, Vec<Gesture>) {
+        let mut next = self.clone();
+        let gestures = next.tracks.entry(hand.index).or_insert_with(Track::default).sense(cage, hand, instant);
+        (next, gestures)
+    }
+
+    // A resting hand sends no new samples on the desktop, so owners should sense its last
+    // position again at this instant to see the dwell.
+    pub fn dwell_due(&self) -> Option<Instant> {
+        self.tracks.values()
+                   .filter(|track| !track.dwelled)
+                   .filter_map(|track| track.rest.map(|(_, rest_instant)| rest_instant + Duration::from_millis(DWELL_MILLIS)))
+                   .min()
+    }
+}
+
+impl<Out> Wail2<Out> where Out: Clone + 'static {
+    pub fn add_gestures(&self) -> Wail2<Vec<Gesture>> {
+        self.add_gestures_with(|gestures, _| gestures)
+    }
+    pub fn add_gestures_with<R, F>(&self, f: F) -> Wail2<R> where R: Clone + 'static, F: Fn(Vec<Gesture>, Out) -> R + 'static {
+        let base_wail = (*self).clone();
+        let gesture_wail = Wail2::create(Rc::new(move || {
+            Box::new(GestureWailing {
+                offset: Offset::default(),
+                mist_id: rand::random::<u64>(),
+                beat_id: rand::random::<u64>(),
+                gestures: Gestures::default(),
+                hands: BTreeMap::new(),
+                base_wailing: base_wail.summon(),
+            }) as Box<Wailing<(Vec<Gesture>, Out)>>
+        }));
+        gesture_wail.map(move |(gestures, out)| f(gestures, out))
+    }
+}
+
+pub struct GestureWailing<BaseOut> {
+    offset: Offset,
+    mist_id: u64,
+    beat_id: u64,
+    gestures: Gestures,
+    hands: BTreeMap<usize, Hand>,
+    base_wailing: Box<Wailing<BaseOut>>,
+}
+
+impl<BaseOut> GestureWailing<BaseOut> {
+    fn cage(&self) -> Cage {
+        Cage::from((self.size(), self.offset))
+    }
+
+    fn sense(&mut self, hand: &Hand, instant: Instant) -> Vec<Gesture> {
+        let (gestures, recognized) = self.gestures.sense(&self.cage(), hand, instant);
+        self.gestures = gestures;
+        recognized
+    }
+}
+
+impl<BaseOut> Wailing<(Vec<Gesture>, BaseOut)> for GestureWailing<BaseOut> {
+    fn size(&self) -> Frame {
+        (*self.base_wailing).size()
+    }
+
+    fn view(&self) -> Vision<WailingIn> {
+        let mut vision = Vision::new();
+        add_hand_mist(&mut vision, self.mist_id, self.cage());
+        // Animation frames can be far apart, so keep ticking a while past the due instant.
+        if let Some(dwell_instant) = self.gestures.dwell_due() {
+            add_tick_beat(&mut vision, self.beat_id, dwell_instant + Duration::from_millis(DWELL_MILLIS));
+        }
+        let base_vision = (*self.base_wailing).view();
+        vision.add_vision(base_vision, |x| Some(x));
+        vision
+    }
+
+    fn update(&mut self, message: &WailingIn) -> (Vec<Gesture>, BaseOut) {
+        match message {
+            &WailingIn::Offset(offset) => {
+                self.offset = offset;
+                (Vec::new(), (*self.base_wailing).update(message))
+            },
+            &WailingIn::Hand(hand) => {
+                self.hands.insert(hand.index, hand);
+                let gestures = self.sense(&hand, Instant::now());
+                (gestures, (*self.base_wailing).update(message))
+            },
+            &WailingIn::Tick => {
+                let now = Instant::now();
+                let hands: Vec<Hand> = self.hands.values().cloned().collect();
+                let mut gestures = Vec::new();
+                for hand in hands {
+                    gestures.extend(self.sense(&hand, now));
+                }
+                (gestures, (*self.base_wailing).update(message))
+            },
+            &WailingIn::Select(_) | &WailingIn::FocusWish(..) => (Vec::new(), (*self.base_wailing).update(message)),
+        }
+    }
+
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        (*self.base_wailing).take_wishes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hand::Hand;
+    use cage::{Cage, Offset};
+    use std::f32::consts::PI;
+    use std::time::{Duration, Instant};
+
+    // Millisecond, x, y, z.
+    type Sample = (u64, f32, f32, f32);
+
+    fn recognize(samples: Vec<Sample>) -> Vec<Gesture> {
+        let cage = Cage::from((-0.5, 0.5, -0.5, 0.5, -0.1, 0.1));
+        let start = Instant::now();
+        let mut gestures = Gestures::default();
+        let mut recognized = Vec::new();
+        for (millis, x, y, z) in samples {
+            let hand = Hand::at(Offset::from((x, y, z)));
+            let (next, found) = gestures.sense(&cage, &hand, start + Duration::from_millis(millis));
+            gestures = next;
+            recognized.extend(found);
+        }
+        recognized
+    }
+
+    #[test]
+    fn quick_pokes_tap_and_double_tap() {
+        let tap = vec![(0, 0.0, 0.0, 0.05), (50, 0.0, 0.0, -0.05), (200, 0.005, 0.0, 0.05)];
+        assert_eq!(vec![Gesture::Tap], recognize(tap));
+        let double_tap = vec![(0, 0.0, 0.0, 0.05), (50, 0.0, 0.0, -0.05), (150, 0.0, 0.0, 0.05),
+                              (250, 0.0, 0.0, -0.05), (350, 0.0, 0.0, 0.05)];
+        assert_eq!(vec![Gesture::Tap, Gesture::DoubleTap], recognize(double_tap));
+        let slow_press = vec![(0, 0.0, 0.0, 0.05), (50, 0.0, 0.0, -0.05), (600, 0.0, 0.0, 0.05)];
+        assert!(!recognize(slow_press).contains(&Gesture::Tap));
+    }
+
+    #[test]
+    fn fast_straight_moves_swipe() {
+        let right = vec![(0, -0.2, 0.0, 0.05), (100, -0.1, 0.01, 0.05), (200, 0.0, 0.0, 0.05)];
+        assert_eq!(vec![Gesture::Swipe(SwipeDirection::Right)], recognize(right));
+        let down = vec![(0, 0.1, 0.2, 0.05), (100, 0.1, 0.1, 0.05), (200, 0.11, 0.0, 0.05)];
+        assert_eq!(vec![Gesture::Swipe(SwipeDirection::Down)], recognize(down));
+        let diagonal = vec![(0, -0.2, -0.2, 0.05), (100, -0.1, -0.1, 0.05), (200, 0.0, 0.0, 0.05)];
+        assert_eq!(Vec::<Gesture>::new(), recognize(diagonal));
+        let slow = vec![(0, -0.2, 0.0, 0.05), (400, -0.1, 0.0, 0.05), (800, 0.0, 0.0, 0.05)];
+        assert_eq!(Vec::<Gesture>::new(), recognize(slow));
+    }
+
+    #[test]
+    fn resting_hand_dwells_once() {
+        let rest = vec![(0, 0.1, 0.1, 0.05), (400, 0.105, 0.1, 0.05), (800, 0.1, 0.1, 0.05), (1200, 0.1, 0.1, 0.05)];
+        assert_eq!(vec![Gesture::Dwell], recognize(rest));
+        let leaves = vec![(0, 0.1, 0.1, 0.05), (400, 0.1, 0.1, 0.05), (500, 0.9, 0.1, 0.05), (800, 0.1, 0.1, 0.05)];
+        assert_eq!(Vec::<Gesture>::new(), recognize(leaves));
+    }
+
+    #[test]
+    fn loops_circle_in_either_direction() {
+        let circle = |clockwise: bool| -> Vec<Sample> {
+            (0..13).map(|step| {
+                let angle = step as f32 * PI / 6.0 * if clockwise { -1.0 } else { 1.0 };
+                (step * 150, 0.1 * angle.cos(), 0.1 * angle.sin(), 0.05)
+            }).collect()
+        };
+        assert_eq!(vec![Gesture::Circle { clockwise: false }], recognize(circle(false)));
+        assert_eq!(vec![Gesture::Circle { clockwise: true }], recognize(circle(true)));
+    }
+
+    #[test]
+    fn hands_are_tracked_apart() {
+        let cage = Cage::from((-0.5, 0.5, -0.5, 0.5, -0.1, 0.1));
+        let start = Instant::now();
+        let first = Hand::at(Offset::from((0.0, 0.0, 0.05)));
+        let second = first.with_index(1);
+        let (gestures, _) = Gestures::default().sense(&cage, &first, start);
+        let (gestures, _) = gestures.sense(&cage, &Hand::at(Offset::from((0.0, 0.0, -0.05))), start + Duration::from_millis(50));
+        let (gestures, found) = gestures.sense(&cage, &second, start + Duration::from_millis(100));
+        assert!(found.is_empty());
+        let (_, found) = gestures.sense(&cage, &first, start + Duration::from_millis(150));
+        assert_eq!(vec![Gesture::Tap], found);
+    }
+}
@@ -0,0 +1,268 @@
+extern crate cage;
+extern crate rand;
+
+use wail::core::{Wail2, Wailing, WailingIn, add_hand_mist};
+use common::Wish;
+use vision::Vision;
+use hand::{Hand, Rotation};
+use mat::{multiply33, transpose33, rotate_vector, IDENTITY33};
+use cage::{Frame, Offset, Cage};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+// Where grabbed content was left. The rotation is the turn of the hand while holding it, on
+// top of any earlier turns, and stays None when the hand reports no rotation. Patches stay
+// square to the axes, so turning the hand swings the content around the grab point without
+// tilting it; apps that can draw it turned may use the rotation themselves.
+#[derive(Copy, Clone, Debug)]
+pub struct Placement {
+    pub offset: Offset,
+    pub rotation: Option<Rotation>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum GrabMsg {
+    None,
+    Moved(Placement),
+    Released(Placement),
+}
+
+impl<Out> Wail2<Out> where Out: Clone + 'static {
+    pub fn add_grab(&self) -> Wail2<GrabMsg> {
+        self.add_grab_with(|grab_msg, _| grab_msg)
+    }
+    pub fn add_grab_with<R, F>(&self, f: F) -> Wail2<R> where R: Clone + 'static, F: Fn(GrabMsg, Out) -> R + 'static {
+        let base_wail = (*self).clone();
+        let grab_wail = Wail2::create(Rc::new(move || {
+            Box::new(GrabWailing {
+                placement: Placement { offset: Offset::default(), rotation: None },
+                mist_id: rand::random::<u64>(),
+                grabbing: BTreeMap::new(),
+                hold: None,
+                base_wailing: base_wail.summon(),
+            }) as Box<Wailing<(GrabMsg, Out)>>
+        }));
+        grab_wail.map(move |(grab_msg, out)| f(grab_msg, out))
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Hold {
+    hand_index: usize,
+    hand_offset: Offset,
+    hand_rotation: Option<Rotation>,
+    placement: Placement,
+}
+
+impl Hold {
+    fn placement_for(&self, hand: &Hand) -> Placement {
+        let turn = match (self.hand_rotation, hand.rotation) {
+            (Some(start), Some(now)) => Some(multiply33(&now, &transpose33(&start))),
+            _ => None,
+        };
+        let (x, y, z) = self.placement.offset.tuple();
+        let (grab_x, grab_y, grab_z) = self.hand_offset.tuple();
+        let from_grab = [x - grab_x, y - grab_y, z - grab_z];
+        let swung = match turn {
+            Some(ref turn) => rotate_vector(turn, &from_grab),
+            None => from_grab,
+        };
+        let rotation = match turn {
+            Some(ref turn) => Some(multiply33(turn, &self.placement.rotation.unwrap_or(IDENTITY33))),
+            None => self.placement.rotation,
+        };
+        Placement { offset: hand.offset.shift(swung[0], swung[1], swung[2]), rotation: rotation }
+    }
+}
+
+// Closing the grab inside the content picks it up. A hand that is already grabbing when it
+// arrives does not, so sweeping a held object through another leaves the other in place.
+// Only the hand holding the content moves or releases it.
+pub struct GrabWailing<BaseOut> {
+    placement: Placement,
+    mist_id: u64,
+    grabbing: BTreeMap<usize, bool>,
+    hold: Option<Hold>,
+    base_wailing: Box<Wailing<BaseOut>>,
+}
+
+impl<BaseOut> GrabWailing<BaseOut> {
+    fn cage(&self) -> Cage {
+        Cage::from((self.size(), self.placement.offset))
+    }
+
+    fn is_picked_up_by(&self, hand: &Hand) -> bool {
+        let (x, y, z) = hand.offset.tuple();
+        let was_grabbing = self.grabbing.get(&hand.index).cloned().unwrap_or(false);
+        hand.grab && !was_grabbing && self.cage().contains(x, y, z)
+    }
+
+    fn place(&mut self, placement: Placement) -> BaseOut {
+        self.placement = placement;
+        (*self.base_wailing).update(&WailingIn::Offset(placement.offset))
+    }
+}
+
+impl<BaseOut> Wailing<(GrabMsg, BaseOut)> for GrabWailing<BaseOut> {
+    fn size(&self) -> Frame {
+        (*self.base_wailing).size()
+    }
+
+    fn view(&self) -> Vision<WailingIn> {
+        let mut vision = Vision::new();
+        add_hand_mist(&mut vision, self.mist_id, self.cage());
+        let base_vision = (*self.base_wailing).view();
+        vision.add_vision(base_vision, |x| Some(x));
+        vision
+    }
+
+    // The base sees no hands while it is held.
+    fn update(&mut self, message: &WailingIn) -> (GrabMsg, BaseOut) {
+        match message {
+            &WailingIn::Offset(offset) => {
+                self.hold = None;
+                (GrabMsg::None, self.place(Placement { offset: offset, rotation: self.placement.rotation }))
+            },
+            &WailingIn::Hand(hand) => {
+                let grab_msg = match self.hold {
+                    Some(hold) if hold.hand_index != hand.index => GrabMsg::None,
+                    Some(hold) => {
+                        let placement = hold.placement_for(&hand);
+                        if hand.grab {
+                            GrabMsg::Moved(placement)
+                        } else {
+                            self.hold = None;
+                            GrabMsg::Released(placement)
+                        }
+                    },
+                    None => {
+                        if self.is_picked_up_by(&hand) {
+                            self.hold = Some(Hold {
+                                hand_index: hand.index,
+                                hand_offset: hand.offset,
+                                hand_rotation: hand.rotation,
+                                placement: self.placement,
+                            });
+                        }
+                        GrabMsg::None
+                    },
+                };
+                self.grabbing.insert(hand.index, hand.grab);
+                let base_out = match grab_msg {
+                    GrabMsg::Moved(placement) => self.place(placement),
+                    GrabMsg::Released(placement) => {
+                        self.place(placement);
+                        (*self.base_wailing).update(message)
+                    },
+                    GrabMsg::None => if self.hold.is_some() {
+                        (*self.base_wailing).update(&WailingIn::Offset(self.placement.offset))
+                    } else {
+                        (*self.base_wailing).update(message)
+                    },
+                };
+                (grab_msg, base_out)
+            },
+            &WailingIn::Tick | &WailingIn::Select(_) | &WailingIn::FocusWish(..) => (GrabMsg::None, (*self.base_wailing).update(message)),
+        }
+    }
+
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        (*self.base_wailing).take_wishes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wail::core::{color_wail, Wailing, WailingIn};
+    use hand::Hand;
+    use cage::{Frame, Offset};
+    use color::RED;
+
+    fn grab_wailing() -> Box<Wailing<GrabMsg>> {
+        let mut wailing = color_wail(RED, Frame::from((0.2, 0.2, 0.2))).add_grab().summon();
+        wailing.update(&WailingIn::Offset(Offset::from((1.0, 0.0, 0.0))));
+        wailing
+    }
+
+    fn hand(x: f32, y: f32, grab: bool) -> WailingIn {
+        WailingIn::Hand(Hand::at(Offset::from((x, y, 0.0))).with_grab(grab))
+    }
+
+    fn near(offset: Offset, x: f32, y: f32) -> bool {
+        (offset.x - x).abs() < 1e-5 && (offset.y - y).abs() < 1e-5 && offset.z.abs() < 1e-5
+    }
+
+    #[test]
+    fn held_content_follows_the_hand_and_reports_release() {
+        let mut wailing = grab_wailing();
+        wailing.update(&hand(1.05, 0.0, false));
+        match wailing.update(&hand(1.05, 0.0, true)) {
+            GrabMsg::None => (),
+            grab_msg => panic!("picked up with {:?}", grab_msg),
+        }
+        match wailing.update(&hand(0.55, 0.3, true)) {
+            GrabMsg::Moved(placement) => assert!(near(placement.offset, 0.5, 0.3)),
+            grab_msg => panic!("not moved: {:?}", grab_msg),
+        }
+        match wailing.update(&hand(0.45, 0.3, false)) {
+            GrabMsg::Released(placement) => {
+                assert!(near(placement.offset, 0.4, 0.3));
+                assert!(placement.rotation.is_none());
+            },
+            grab_msg => panic!("not released: {:?}", grab_msg),
+        }
+        let (left, _, _, _, _, _) = wailing.view().mists.values().next().unwrap().cage().limits();
+        assert!((left - 0.3).abs() < 1e-5);
+    }
+
+    #[test]
+    fn grabs_must_start_inside() {
+        let mut wailing = grab_wailing();
+        wailing.update(&hand(0.0, 0.0, true));
+        wailing.update(&hand(1.0, 0.0, true));
+        match wailing.update(&hand(1.2, 0.0, true)) {
+            GrabMsg::None => (),
+            grab_msg => panic!("carried off with {:?}", grab_msg),
+        }
+    }
+
+    #[test]
+    fn turning_the_hand_swings_content_around_the_grab_point() {
+        let mut wailing = grab_wailing();
+        let quarter_turn = [[0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+        let at_edge = Hand::at(Offset::from((0.9, 0.0, 0.0))).with_rotation(Some(IDENTITY33));
+        wailing.update(&WailingIn::Hand(at_edge.with_grab(true)));
+        match wailing.update(&WailingIn::Hand(at_edge.with_grab(true).with_rotation(Some(quarter_turn)))) {
+            GrabMsg::Moved(placement) => {
+                assert!(near(placement.offset, 0.9, 0.1));
+                assert_eq!(Some(quarter_turn), placement.rotation);
+            },
+            grab_msg => panic!("not moved: {:?}", grab_msg),
+        }
+    }
+
+    #[test]
+    fn only_the_holding_hand_moves_or_releases() {
+        let mut wailing = grab_wailing();
+        let other = |x, grab| WailingIn::Hand(Hand::at(Offset::from((x, 0.0, 0.0))).with_index(1).with_grab(grab));
+        wailing.update(&hand(1.05, 0.0, true));
+        match wailing.update(&other(3.0, false)) {
+            GrabMsg::None => (),
+            grab_msg => panic!("other hand gave {:?}", grab_msg),
+        }
+        wailing.update(&other(1.0, true));
+        match wailing.update(&hand(1.55, 0.0, true)) {
+            GrabMsg::Moved(placement) => assert!(near(placement.offset, 1.5, 0.0)),
+            grab_msg => panic!("not moved: {:?}", grab_msg),
+        }
+        match wailing.update(&other(1.5, false)) {
+            GrabMsg::None => (),
+            grab_msg => panic!("other hand gave {:?}", grab_msg),
+        }
+        match wailing.update(&hand(1.55, 0.0, false)) {
+            GrabMsg::Released(_) => (),
+            grab_msg => panic!("not released: {:?}", grab_msg),
+        }
+    }
+}
@@ -0,0 +1,436 @@
+extern crate cage;
+
+use wail::core::{Wail2, Wailing, WailingIn};
+use wail::button::{button_wail, ButtonMsg};
+use wail::layout::{row, column, offset_to_fill, arranged_size, Arrangement, Layout};
+use vision::Vision;
+use hand::Hand;
+use common::{KeyPress, KeyCode};
+use cage::{Frame, Offset, Cage};
+use std::rc::Rc;
+use std::collections::HashMap;
+
+const KEY_UNIT: f32 = 0.06;
+const KEY_DEPTH: f32 = 0.03;
+const KEY_SPACING: f32 = 0.008;
+
+// What a keyboard asks of the text it is typing into. Select moves the caret like Move
+// while keeping the other end of the selection where it was.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TextEdit {
+    Insert(char),
+    Backspace,
+    Delete,
+    Enter,
+    Move(Motion),
+    Select(Motion),
+    SelectAll,
+}
+
+impl TextEdit {
+    // Characters arrive separately as ReceiveChar, so only editing keys map here.
+    pub fn from_key_press(key_press: KeyPress) -> Option<TextEdit> {
+        let motion = match key_press.code {
+            KeyCode::Left => Motion::Left,
+            KeyCode::Right => Motion::Right,
+            KeyCode::Up => Motion::Up,
+            KeyCode::Down => Motion::Down,
+            KeyCode::Home => Motion::Home,
+            KeyCode::End => Motion::End,
+            KeyCode::Backspace => return Some(TextEdit::Backspace),
+            KeyCode::Delete => return Some(TextEdit::Delete),
+            KeyCode::Enter => return Some(TextEdit::Enter),
+            _ => return None,
+        };
+        if key_press.shift {
+            Some(TextEdit::Select(motion))
+        } else {
+            Some(TextEdit::Move(motion))
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum KeyboardLayout {
+    Qwerty,
+    Numeric,
+    Symbol,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum KeyboardKey {
+    Char(char),
+    Space,
+    Shift,
+    Caps,
+    Backspace,
+    Enter,
+    Layout(KeyboardLayout),
+}
+
+// Shift lasts for one character; caps stays on until pressed again. With both on, letters
+// come out lower case.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KeyboardState {
+    pub layout: KeyboardLayout,
+    pub shift: bool,
+    pub caps: bool,
+}
+
+impl Default for KeyboardState {
+    fn default() -> Self {
+        KeyboardState { layout: KeyboardLayout::Qwerty, shift: false, caps: false }
+    }
+}
+
+impl KeyboardState {
+    pub fn is_upper(&self) -> bool {
+        self.shift != self.caps
+    }
+
+    pub fn press(&self, key: KeyboardKey) -> (KeyboardState, Option<TextEdit>) {
+        match key {
+            KeyboardKey::Char(c) => {
+                let c = if self.is_upper() { c.to_ascii_uppercase() } else { c };
+                (KeyboardState { shift: false, ..*self }, Some(TextEdit::Insert(c)))
+            },
+            KeyboardKey::Space => (KeyboardState { shift: false, ..*self }, Some(TextEdit::Insert(' '))),
+            KeyboardKey::Shift => (KeyboardState { shift: !self.shift, ..*self }, None),
+            KeyboardKey::Caps => (KeyboardState { caps: !self.caps, shift: false, ..*self }, None),
+            KeyboardKey::Backspace => (*self, Some(TextEdit::Backspace)),
+            KeyboardKey::Enter => (*self, Some(TextEdit::Enter)),
+            KeyboardKey::Layout(layout) => (KeyboardState { layout: layout, shift: false, ..*self }, None),
+        }
+    }
+}
+
+fn char_keys(chars: &str) -> Vec<KeyboardKey> {
+    chars.chars().map(KeyboardKey::Char).collect()
+}
+
+fn with_keys(mut first: Vec<KeyboardKey>, rest: Vec<KeyboardKey>) -> Vec<KeyboardKey> {
+    first.extend(rest);
+    first
+}
+
+pub fn key_rows(layout: KeyboardLayout) -> Vec<Vec<KeyboardKey>> {
+    match layout {
+        KeyboardLayout::Qwerty => vec![
+            char_keys("qwertyuiop"),
+            with_keys(vec![KeyboardKey::Caps], char_keys("asdfghjkl")),
+            with_keys(with_keys(vec![KeyboardKey::Shift], char_keys("zxcvbnm")), vec![KeyboardKey::Backspace]),
+            vec![KeyboardKey::Layout(KeyboardLayout::Numeric), KeyboardKey::Layout(KeyboardLayout::Symbol), KeyboardKey::Space, KeyboardKey::Enter],
+        ],
+        KeyboardLayout::Numeric => vec![
+            char_keys("123"),
+            char_keys("456"),
+            char_keys("789"),
+            with_keys(char_keys(".0"), vec![KeyboardKey::Backspace]),
+            vec![KeyboardKey::Layout(KeyboardLayout::Qwerty), KeyboardKey::Enter],
+        ],
+        KeyboardLayout::Symbol => vec![
+            char_keys("!@#$%^&*()"),
+            char_keys("-_=+[]{};:"),
+            char_keys("'\"<>,.?/\\|"),
+            vec![KeyboardKey::Layout(KeyboardLayout::Qwerty), KeyboardKey::Char('~'), KeyboardKey::Char('`'), KeyboardKey::Space, KeyboardKey::Backspace, KeyboardKey::Enter],
+        ],
+    }
+}
+
+pub fn key_label(key: KeyboardKey, upper: bool) -> String {
+    match key {
+        KeyboardKey::Char(c) => if upper { c.to_ascii_uppercase().to_string() } else { c.to_string() },
+        KeyboardKey::Space => "space".to_string(),
+        KeyboardKey::Shift => "shift".to_string(),
+        KeyboardKey::Caps => "caps".to_string(),
+        KeyboardKey::Backspace => "del".to_string(),
+        KeyboardKey::Enter => "enter".to_string(),
+        KeyboardKey::Layout(KeyboardLayout::Qwerty) => "abc".to_string(),
+        KeyboardKey::Layout(KeyboardLayout::Numeric) => "123".to_string(),
+        KeyboardKey::Layout(KeyboardLayout::Symbol) => "#+=".to_string(),
+    }
+}
+
+fn key_frame(key: KeyboardKey) -> Frame {
+    let units = match key {
+        KeyboardKey::Char(_) => 1.0,
+        KeyboardKey::Space => 4.0,
+        KeyboardKey::Layout(_) | KeyboardKey::Shift | KeyboardKey::Caps => 1.5,
+        KeyboardKey::Backspace | KeyboardKey::Enter => 2.0,
+    };
+    Frame::from((KEY_UNIT * units, KEY_UNIT, KEY_DEPTH))
+}
+
+fn first_key(keys: Vec<Option<KeyboardKey>>) -> Option<KeyboardKey> {
+    keys.into_iter().filter_map(|key| key).next()
+}
+
+fn keys_layout() -> Layout {
+    Layout::default().with_spacing(KEY_SPACING)
+}
+
+// The size keys_wail arranges a layout's rows into.
+fn keys_size(keyboard_layout: KeyboardLayout) -> Frame {
+    let layout = keys_layout();
+    let row_sizes = key_rows(keyboard_layout).into_iter().map(|keys| {
+        arranged_size(Arrangement::Row, &layout, &keys.into_iter().map(key_frame).collect())
+    }).collect();
+    arranged_size(Arrangement::Column, &layout, &row_sizes)
+}
+
+// Rows of buttons, one per key, reporting whichever key was clicked.
+pub fn keys_wail(state: &KeyboardState) -> Wail2<Option<KeyboardKey>> {
+    let layout = keys_layout();
+    let rows = key_rows(state.layout).into_iter().map(|keys| {
+        let buttons = keys.into_iter().map(|key| {
+            button_wail(&key_label(key, state.is_upper()), key_frame(key)).map(move |button_msg| {
+                if button_msg == ButtonMsg::Clicked { Some(key) } else { None }
+            })
+        }).collect();
+        row(buttons, layout).map(first_key)
+    }).collect();
+    column(rows, layout).map(first_key)
+}
+
+pub fn keyboard_wail() -> Wail2<Option<TextEdit>> {
+    Wail2::create(Rc::new(move || {
+        Box::new(KeyboardWailing::new()) as Box<Wailing<Option<TextEdit>>>
+    }))
+}
+
+// Each layout and case gets its own set of keys, summoned the first time it shows and kept
+// after, so a key's ids stay the same whenever its set comes back. The keyboard keeps the
+// size of its largest layout so parents need not lay it out again.
+pub struct KeyboardWailing {
+    state: KeyboardState,
+    frame: Frame,
+    offset: Offset,
+    key_sets: HashMap<(KeyboardLayout, bool), Box<Wailing<Option<KeyboardKey>>>>,
+    last_hand: Option<Hand>,
+}
+
+impl KeyboardWailing {
+    fn new() -> Self {
+        let layouts = vec![KeyboardLayout::Qwerty, KeyboardLayout::Numeric, KeyboardLayout::Symbol];
+        let frame = layouts.into_iter().fold(Frame::from((0.0, 0.0, 0.0)), |frame, layout| {
+            let size = keys_size(layout);
+            Frame::from((frame.w.max(size.w), frame.h.max(size.h), frame.d.max(size.d)))
+        });
+        let mut keyboard = KeyboardWailing {
+            state: KeyboardState::default(),
+            frame: frame,
+            offset: Offset::default(),
+            key_sets: HashMap::new(),
+            last_hand: None,
+        };
+        keyboard.show_keys();
+        keyboard
+    }
+
+    fn keys(&mut self) -> &mut Box<Wailing<Option<KeyboardKey>>> {
+        let state = self.state;
+        self.key_sets.entry((state.layout, state.is_upper())).or_insert_with(|| keys_wail(&state).summon())
+    }
+
+    fn place_keys(&mut self) {
+        let (left, right, bottom, top, _, near) = Cage::from((self.frame, self.offset)).limits();
+        let keys = self.keys();
+        let size = keys.size();
+        let (center_x, center_y) = ((left + right) / 2.0, (bottom + top) / 2.0);
+        let target = Cage::from((center_x - size.w / 2.0, center_x + size.w / 2.0,
+                                 center_y - size.h / 2.0, center_y + size.h / 2.0, near - size.d, near));
+        keys.update(&WailingIn::Offset(offset_to_fill(size, &target)));
+    }
+
+    fn press(&mut self, key: Option<KeyboardKey>) -> Option<TextEdit> {
+        match key {
+            Some(key) => {
+                let (state, text_edit) = self.state.press(key);
+                if state != self.state {
+                    self.state = state;
+                    self.show_keys();
+                }
+                text_edit
+            },
+            None => None,
+        }
+    }
+
+    // Keys coming back see the current hand once before they report anything, so the key
+    // that switched sets is not clicked again while it is still held.
+    fn show_keys(&mut self) {
+        self.place_keys();
+        if let Some(hand) = self.last_hand {
+            self.keys().update(&WailingIn::Hand(hand));
+        }
+    }
+}
+
+impl Wailing<Option<TextEdit>> for KeyboardWailing {
+    fn size(&self) -> Frame {
+        self.frame
+    }
+
+    fn view(&self) -> Vision<WailingIn> {
+        match self.key_sets.get(&(self.state.layout, self.state.is_upper())) {
+            Some(keys) => keys.view(),
+            None => Vision::new(),
+        }
+    }
+
+    fn update(&mut self, message: &WailingIn) -> Option<TextEdit> {
+        match message {
+            &WailingIn::Offset(offset) => {
+                self.offset = offset;
+                self.place_keys();
+                None
+            },
+            &WailingIn::Hand(hand) => {
+                self.last_hand = Some(hand);
+                let key = self.keys().update(message);
+                self.press(key)
+            },
+            &WailingIn::Select(_) => {
+                let key = self.keys().update(message);
+                self.press(key)
+            },
+            &WailingIn::Tick | &WailingIn::FocusWish(..) => {
+                self.keys().update(message);
+                None
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cage::Offset;
+    use hand::Hand;
+    use wail::core::{Wailing, WailingIn};
+    use patch::Patch;
+
+    fn label(keyboard: &KeyboardWailing, glyph: char) -> Patch {
+        *keyboard.view().patches.values().find(|patch| patch.glyph == glyph).unwrap()
+    }
+
+    // The shift key's label shares its letters with letter keys, so find it as the 's' on
+    // the same row as 'z'.
+    fn shift_label(keyboard: &KeyboardWailing) -> Patch {
+        let middle = |patch: &Patch| (patch.position.bottom + patch.position.top) / 2.0;
+        let z_middle = middle(&label(keyboard, 'z'));
+        *keyboard.view().patches.values()
+                 .find(|patch| patch.glyph == 's' && (middle(patch) - z_middle).abs() < 1e-5)
+                 .unwrap()
+    }
+
+    // A hand over the label, first resting on the key's face and then pushed in.
+    fn poke(keyboard: &mut KeyboardWailing, label: Patch) -> Option<TextEdit> {
+        let position = label.position;
+        let (x, y) = ((position.left + position.right) / 2.0, (position.bottom + position.top) / 2.0);
+        let resting = Hand::at(Offset::from((x, y, position.near)));
+        let pushed = Hand::at(Offset::from((x, y, position.near - 0.005)));
+        let away = Hand::at(Offset::from((x, y, position.near + 1.0)));
+        keyboard.update(&WailingIn::Hand(resting));
+        let text_edit = keyboard.update(&WailingIn::Hand(pushed));
+        keyboard.update(&WailingIn::Hand(away));
+        text_edit
+    }
+
+    #[test]
+    fn shift_lasts_one_character_and_caps_stays() {
+        let state = KeyboardState::default();
+        let (shifted, _) = state.press(KeyboardKey::Shift);
+        let (after, text_edit) = shifted.press(KeyboardKey::Char('a'));
+        assert_eq!(Some(TextEdit::Insert('A')), text_edit);
+        assert!(!after.is_upper());
+        let (capped, _) = state.press(KeyboardKey::Caps);
+        let (still_capped, text_edit) = capped.press(KeyboardKey::Char('b'));
+        assert_eq!(Some(TextEdit::Insert('B')), text_edit);
+        assert!(still_capped.is_upper());
+        let (shifted_caps, _) = still_capped.press(KeyboardKey::Shift);
+        assert_eq!(Some(TextEdit::Insert('c')), shifted_caps.press(KeyboardKey::Char('c')).1);
+    }
+
+    #[test]
+    fn layouts_switch_and_edit_keys_report() {
+        let state = KeyboardState::default();
+        let (numeric, text_edit) = state.press(KeyboardKey::Layout(KeyboardLayout::Numeric));
+        assert_eq!(None, text_edit);
+        assert_eq!(KeyboardLayout::Numeric, numeric.layout);
+        assert_eq!(char_keys("123"), key_rows(numeric.layout)[0]);
+        assert_eq!(Some(TextEdit::Backspace), numeric.press(KeyboardKey::Backspace).1);
+        assert_eq!(Some(TextEdit::Enter), numeric.press(KeyboardKey::Enter).1);
+        assert_eq!(Some(TextEdit::Insert(' ')), state.press(KeyboardKey::Space).1);
+    }
+
+    #[test]
+    fn poking_keys_types_and_shift_relabels() {
+        let mut keyboard = KeyboardWailing::new();
+        let q = label(&keyboard, 'q');
+        assert_eq!(Some(TextEdit::Insert('q')), poke(&mut keyboard, q));
+        let shift = shift_label(&keyboard);
+        assert_eq!(None, poke(&mut keyboard, shift));
+        assert!(keyboard.view().patches.values().any(|patch| patch.glyph == 'Q'));
+        let w = label(&keyboard, 'W');
+        assert_eq!(Some(TextEdit::Insert('W')), poke(&mut keyboard, w));
+        assert!(keyboard.view().patches.values().any(|patch| patch.glyph == 'w'));
+    }
+
+    #[test]
+    fn gaze_selecting_a_key_types_it() {
+        let mut keyboard = KeyboardWailing::new();
+        let q = label(&keyboard, 'q').position;
+        let (x, y) = ((q.left + q.right) / 2.0, (q.bottom + q.top) / 2.0);
+        let mist_id = *keyboard.view().mists.iter()
+                               .find(|&(_, mist)| mist.contains(x, y, q.near))
+                               .map(|(id, _)| id).unwrap();
+        assert_eq!(Some(TextEdit::Insert('q')), keyboard.update(&WailingIn::Select(mist_id)));
+    }
+
+    #[test]
+    fn measured_sizes_match_summoned_keys() {
+        for layout in vec![KeyboardLayout::Qwerty, KeyboardLayout::Numeric, KeyboardLayout::Symbol] {
+            let summoned = keys_wail(&KeyboardState { layout: layout, ..Default::default() }).summon().size();
+            let measured = keys_size(layout);
+            assert!((summoned.w - measured.w).abs() < 1e-6 && (summoned.h - measured.h).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn key_ids_come_back_with_their_set() {
+        let mut keyboard = KeyboardWailing::new();
+        let ids = |keyboard: &KeyboardWailing| {
+            let mut ids: Vec<u64> = keyboard.view().patches.keys().cloned().collect();
+            ids.sort();
+            ids
+        };
+        let lower_ids = ids(&keyboard);
+        let shift = shift_label(&keyboard);
+        poke(&mut keyboard, shift);
+        assert!(!keyboard.view().patches.values().any(|patch| patch.glyph == 'q'));
+        poke(&mut keyboard, shift);
+        assert_eq!(lower_ids, ids(&keyboard));
+    }
+
+    #[test]
+    fn physical_editing_keys_map_to_edits() {
+        let press = |code, shift| TextEdit::from_key_press(KeyPress { code: code, shift: shift });
+        assert_eq!(Some(TextEdit::Move(Motion::Left)), press(KeyCode::Left, false));
+        assert_eq!(Some(TextEdit::Select(Motion::End)), press(KeyCode::End, true));
+        assert_eq!(Some(TextEdit::Backspace), press(KeyCode::Backspace, true));
+        assert_eq!(Some(TextEdit::Enter), press(KeyCode::Enter, false));
+        assert_eq!(None, press(KeyCode::Other(30), false));
+        assert_eq!(None, press(KeyCode::Tab, false));
+    }
+}
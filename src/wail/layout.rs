@@ -0,0 +1,310 @@
+extern crate cage;
+
+use wail::core::{Wail2, Wailing, WailingIn};
+use common::Wish;
+use vision::Vision;
+use cage::{Frame, Offset, Cage};
+use theme;
+use std::rc::Rc;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Arrangement {
+    // Left to right.
+    Row,
+    // Top to bottom.
+    Column,
+    // Left to right, then top to bottom, in equal cells with this many columns.
+    Grid(usize),
+    // Front to back, each child behind the one before it.
+    Stack,
+}
+
+// Rows and columns align children on the cross axis, with Start being the top of a row and
+// the left of a column. Grids and stacks align each child in both directions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Layout {
+    pub spacing: f32,
+    pub padding: f32,
+    pub align: Align,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout { spacing: theme::current().spacing, padding: 0.0, align: Align::Center }
+    }
+}
+
+impl Layout {
+    pub fn with_spacing(&self, spacing: f32) -> Self {
+        Layout { spacing: spacing, ..*self }
+    }
+    pub fn with_padding(&self, padding: f32) -> Self {
+        Layout { padding: padding, ..*self }
+    }
+    pub fn with_align(&self, align: Align) -> Self {
+        Layout { align: align, ..*self }
+    }
+}
+
+pub fn row<Out>(children: Vec<Wail2<Out>>, layout: Layout) -> Wail2<Vec<Out>> where Out: Clone + 'static {
+    arranged(Arrangement::Row, children, layout)
+}
+
+pub fn column<Out>(children: Vec<Wail2<Out>>, layout: Layout) -> Wail2<Vec<Out>> where Out: Clone + 'static {
+    arranged(Arrangement::Column, children, layout)
+}
+
+pub fn grid<Out>(columns: usize, children: Vec<Wail2<Out>>, layout: Layout) -> Wail2<Vec<Out>> where Out: Clone + 'static {
+    arranged(Arrangement::Grid(columns.max(1)), children, layout)
+}
+
+pub fn stack<Out>(children: Vec<Wail2<Out>>, layout: Layout) -> Wail2<Vec<Out>> where Out: Clone + 'static {
+    arranged(Arrangement::Stack, children, layout)
+}
+
+pub fn arranged<Out>(arrangement: Arrangement, children: Vec<Wail2<Out>>, layout: Layout) -> Wail2<Vec<Out>>
+    where Out: Clone + 'static
+{
+    Wail2::create(Rc::new(move || {
+        let child_wailings: Vec<Box<Wailing<Out>>> = children.iter().map(|child| child.summon()).collect();
+        let mut wailing = LayoutWailing {
+            arrangement: arrangement,
+            layout: layout,
+            children: child_wailings,
+        };
+        wailing.update(&WailingIn::Offset(Offset::default()));
+        Box::new(wailing) as Box<Wailing<Vec<Out>>>
+    }))
+}
+
+fn grid_shape(count: usize, columns: usize) -> (usize, usize) {
+    let columns = columns.max(1).min(count.max(1));
+    (columns, (count + columns - 1) / columns)
+}
+
+fn max_frame(sizes: &Vec<Frame>) -> Frame {
+    sizes.iter().fold(Frame::from((0.0, 0.0, 0.0)), |max, size| {
+        Frame::from((max.w.max(size.w), max.h.max(size.h), max.d.max(size.d)))
+    })
+}
+
+fn gaps(count: usize, spacing: f32) -> f32 {
+    if count > 1 { (count - 1) as f32 * spacing } else { 0.0 }
+}
+
+pub fn arranged_size(arrangement: Arrangement, layout: &Layout, sizes: &Vec<Frame>) -> Frame {
+    let max = max_frame(sizes);
+    let (pad, count) = (layout.padding * 2.0, sizes.len());
+    match arrangement {
+        Arrangement::Row => {
+            let width = sizes.iter().fold(0.0, |sum, size| sum + size.w) + gaps(count, layout.spacing);
+            Frame::from((width + pad, max.h + pad, max.d))
+        },
+        Arrangement::Column => {
+            let height = sizes.iter().fold(0.0, |sum, size| sum + size.h) + gaps(count, layout.spacing);
+            Frame::from((max.w + pad, height + pad, max.d))
+        },
+        Arrangement::Grid(columns) => {
+            let (columns, rows) = grid_shape(count, columns);
+            Frame::from((max.w * columns as f32 + gaps(columns, layout.spacing) + pad,
+                         max.h * rows as f32 + gaps(rows, layout.spacing) + pad,
+                         max.d))
+        },
+        Arrangement::Stack => {
+            let depth = sizes.iter().fold(0.0, |sum, size| sum + size.d) + gaps(count, layout.spacing);
+            Frame::from((max.w + pad, max.h + pad, depth))
+        },
+    }
+}
+
+// Start of a child of the given length placed in the span from low to high.
+fn aligned(align: Align, low: f32, high: f32, length: f32, start_is_high: bool) -> f32 {
+    let (start, end) = if start_is_high { (high - length, low) } else { (low, high - length) };
+    match align {
+        Align::Start => start,
+        Align::Center => (low + high - length) / 2.0,
+        Align::End => end,
+    }
+}
+
+// The cage each child should fill when the arrangement occupies the given cage. Children
+// keep their own sizes and line up their near faces with the arrangement's, except in stacks.
+pub fn arranged_cages(arrangement: Arrangement, layout: &Layout, sizes: &Vec<Frame>, cage: &Cage) -> Vec<Cage> {
+    let (l, r, b, t, _, n) = cage.limits();
+    let pad = layout.padding;
+    let (left, right, bottom, top) = (l + pad, r - pad, b + pad, t - pad);
+    let mut cages = Vec::with_capacity(sizes.len());
+    match arrangement {
+        Arrangement::Row => {
+            let mut x = left;
+            for size in sizes {
+                let y = aligned(layout.align, bottom, top, size.h, true);
+                cages.push(Cage::from((x, x + size.w, y, y + size.h, n - size.d, n)));
+                x += size.w + layout.spacing;
+            }
+        },
+        Arrangement::Column => {
+            let mut y = top;
+            for size in sizes {
+                let x = aligned(layout.align, left, right, size.w, false);
+                cages.push(Cage::from((x, x + size.w, y - size.h, y, n - size.d, n)));
+                y -= size.h + layout.spacing;
+            }
+        },
+        Arrangement::Grid(columns) => {
+            let (columns, _) = grid_shape(sizes.len(), columns);
+            let cell = max_frame(sizes);
+            for (i, size) in sizes.iter().enumerate() {
+                let (column, row) = (i % columns, i / columns);
+                let cell_left = left + column as f32 * (cell.w + layout.spacing);
+                let cell_top = top - row as f32 * (cell.h + layout.spacing);
+                let x = aligned(layout.align, cell_left, cell_left + cell.w, size.w, false);
+                let y = aligned(layout.align, cell_top - cell.h, cell_top, size.h, true);
+                cages.push(Cage::from((x, x + size.w, y, y + size.h, n - size.d, n)));
+            }
+        },
+        Arrangement::Stack => {
+            let mut z = n;
+            for size in sizes {
+                let x = aligned(layout.align, left, right, size.w, false);
+                let y = aligned(layout.align, bottom, top, size.h, true);
+                cages.push(Cage::from((x, x + size.w, y, y + size.h, z - size.d, z)));
+                z -= size.d + layout.spacing;
+            }
+        },
+    }
+    cages
+}
+
+// Offsets only translate a cage, so the offset that lands a frame on a target is the
+// difference between the target's limits and the frame's limits at the default offset.
+pub fn offset_to_fill(frame: Frame, target: &Cage) -> Offset {
+    let (l0, _, b0, _, _, n0) = Cage::from((frame, Offset::default())).limits();
+    let (l, _, b, _, _, n) = target.limits();
+    Offset::from((l - l0, b - b0, n - n0))
+}
+
+pub struct LayoutWailing<Out> {
+    arrangement: Arrangement,
+    layout: Layout,
+    children: Vec<Box<Wailing<Out>>>,
+}
+
+impl<Out> LayoutWailing<Out> {
+    fn child_sizes(&self) -> Vec<Frame> {
+        self.children.iter().map(|child| child.size()).collect()
+    }
+}
+
+impl<Out> Wailing<Vec<Out>> for LayoutWailing<Out> {
+    fn size(&self) -> Frame {
+        arranged_size(self.arrangement, &self.layout, &self.child_sizes())
+    }
+
+    fn view(&self) -> Vision<WailingIn> {
+        let mut vision = Vision::new();
+        for child in &self.children {
+            vision.add_vision(child.view(), |x| Some(x));
+        }
+        vision
+    }
+
+    fn update(&mut self, message: &WailingIn) -> Vec<Out> {
+        if let &WailingIn::Offset(offset) = message {
+            let sizes = self.child_sizes();
+            let cage = Cage::from((self.size(), offset));
+            let cages = arranged_cages(self.arrangement, &self.layout, &sizes, &cage);
+            self.children.iter_mut().zip(sizes.into_iter().zip(cages.iter()))
+                .map(|(child, (size, child_cage))| {
+                    child.update(&WailingIn::Offset(offset_to_fill(size, child_cage)))
+                })
+                .collect()
+        } else {
+            self.children.iter_mut().map(|child| child.update(message)).collect()
+        }
+    }
+
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        self.children.iter_mut().flat_map(|child| child.take_wishes()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cage::{Cage, Frame};
+
+    fn layout() -> Layout {
+        Layout { spacing: 0.1, padding: 0.05, align: Align::Center }
+    }
+
+    fn sizes() -> Vec<Frame> {
+        vec![Frame::from((0.2, 0.2, 0.1)), Frame::from((0.4, 0.1, 0.2))]
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    fn assert_limits(expected: (f32, f32, f32, f32, f32, f32), cage: &Cage) {
+        let actual = cage.limits();
+        assert!(close(expected.0, actual.0) && close(expected.1, actual.1) && close(expected.2, actual.2)
+                && close(expected.3, actual.3) && close(expected.4, actual.4) && close(expected.5, actual.5),
+                "expected {:?}, got {:?}", expected, actual);
+    }
+
+    #[test]
+    fn row_size_adds_widths_spacing_and_padding() {
+        let size = arranged_size(Arrangement::Row, &layout(), &sizes());
+        assert!(close(0.8, size.w) && close(0.3, size.h) && close(0.2, size.d));
+    }
+
+    #[test]
+    fn row_places_children_left_to_right() {
+        let cage = Cage::from((0.0, 0.8, 0.0, 0.3, -0.2, 0.0));
+        let cages = arranged_cages(Arrangement::Row, &layout(), &sizes(), &cage);
+        assert_limits((0.05, 0.25, 0.05, 0.25, -0.1, 0.0), &cages[0]);
+        assert_limits((0.35, 0.75, 0.1, 0.2, -0.2, 0.0), &cages[1]);
+    }
+
+    #[test]
+    fn column_aligns_start_to_the_left() {
+        let layout = layout().with_align(Align::Start);
+        let cage = Cage::from((0.0, 0.5, 0.0, 0.5, -0.2, 0.0));
+        let cages = arranged_cages(Arrangement::Column, &layout, &sizes(), &cage);
+        assert_limits((0.05, 0.25, 0.25, 0.45, -0.1, 0.0), &cages[0]);
+        assert_limits((0.05, 0.45, 0.05, 0.15, -0.2, 0.0), &cages[1]);
+    }
+
+    #[test]
+    fn grid_uses_equal_cells() {
+        let sizes = vec![Frame::from((0.2, 0.2, 0.1)); 3];
+        let layout = Layout { spacing: 0.1, padding: 0.0, align: Align::Center };
+        let size = arranged_size(Arrangement::Grid(2), &layout, &sizes);
+        assert!(close(0.5, size.w) && close(0.5, size.h));
+        let cages = arranged_cages(Arrangement::Grid(2), &layout, &sizes, &Cage::from((0.0, 0.5, 0.0, 0.5, -0.1, 0.0)));
+        assert_limits((0.3, 0.5, 0.3, 0.5, -0.1, 0.0), &cages[1]);
+        assert_limits((0.0, 0.2, 0.0, 0.2, -0.1, 0.0), &cages[2]);
+    }
+
+    #[test]
+    fn stack_puts_later_children_behind() {
+        let cages = arranged_cages(Arrangement::Stack, &layout(), &sizes(), &Cage::from((0.0, 0.5, 0.0, 0.3, -0.4, 0.0)));
+        assert_limits((0.15, 0.35, 0.05, 0.25, -0.1, 0.0), &cages[0]);
+        assert_limits((0.05, 0.45, 0.1, 0.2, -0.4, -0.2), &cages[1]);
+    }
+
+    #[test]
+    fn offset_to_fill_lands_on_target() {
+        let target = Cage::from((1.0, 1.2, 2.0, 2.2, -0.1, 0.0));
+        let frame = Frame::from((0.2, 0.2, 0.1));
+        assert_limits(target.limits(), &Cage::from((frame, offset_to_fill(frame, &target))));
+    }
+}
@@ -2,5 +2,25 @@ extern crate cage;
 extern crate rand;
 
 #[macro_use] mod core;
+mod button;
+mod slider;
+mod dial;
+mod layout;
+mod bridge;
+mod scroll;
+mod keyboard;
+mod grab;
+mod stretch;
+mod gesture;
 
 pub use wail::core::*;
+pub use wail::button::*;
+pub use wail::slider::*;
+pub use wail::dial::*;
+pub use wail::layout::*;
+pub use wail::bridge::*;
+pub use wail::scroll::*;
+pub use wail::keyboard::*;
+pub use wail::grab::*;
+pub use wail::stretch::*;
+pub use wail::gesture::*;
@@ -0,0 +1,376 @@
+extern crate cage;
+extern crate rand;
+
+use wail::core::{Wail2, Wailing, WailingIn, add_hand_mist, add_tick_beat};
+use wail::button::{button_state, default_press_depth, ButtonState};
+use wail::bridge::WailStar;
+use wail::layout::offset_to_fill;
+use common::Wish;
+use vision::Vision;
+use hand::{Hand, HandLock};
+use cage::{Frame, Offset, Cage};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+// The hand must travel this far while pressed before a press becomes a drag, so items
+// under the hand can still be pressed without the list moving.
+const DRAG_SLOP: f32 = 0.01;
+// Coasting speed falls by this factor per second, and stops below STOP_SPEED.
+const DECAY_PER_SECOND: f32 = 4.0;
+const STOP_SPEED: f32 = 0.02;
+const BEAT_MILLIS: u64 = 100;
+
+// A vertical list of item_count items, each item_height tall, seen through a viewport.
+// Items are created when they scroll into view and dropped when they leave it. The output
+// pairs each live item's index with what it returned.
+pub fn scroll_wail<Out, F>(viewport: Frame, item_count: usize, item_height: f32, make_item: F) -> Wail2<Vec<(usize, Out)>>
+    where Out: Clone + 'static, F: Fn(usize) -> Wail2<Out> + 'static
+{
+    let make_item: Rc<Fn(usize) -> Wail2<Out>> = Rc::new(make_item);
+    Wail2::create(Rc::new(move || {
+        Box::new(ScrollWailing::new(viewport, item_count, item_height, make_item.clone())) as Box<Wailing<Vec<(usize, Out)>>>
+    }))
+}
+
+pub fn scroll_star<Out, F>(offset: Offset, viewport: Frame, item_count: usize, item_height: f32, make_item: F)
+    -> WailStar<Vec<(usize, Out)>> where Out: Clone + 'static, F: Fn(usize) -> Wail2<Out> + 'static
+{
+    scroll_wail(viewport, item_count, item_height, make_item).to_star(offset)
+}
+
+// Indices of the items at least partly inside a viewport scrolled down by scroll, as a
+// half-open range.
+pub fn visible_range(scroll: f32, viewport_height: f32, item_height: f32, item_count: usize) -> (usize, usize) {
+    if item_height <= 0.0 {
+        return (0, 0);
+    }
+    let first = (scroll / item_height).floor().max(0.0) as usize;
+    let end = ((scroll + viewport_height) / item_height).ceil().max(0.0) as usize;
+    (first.min(item_count), end.min(item_count))
+}
+
+pub fn max_scroll(viewport_height: f32, item_height: f32, item_count: usize) -> f32 {
+    (item_height * item_count as f32 - viewport_height).max(0.0)
+}
+
+// Advances a released list by seconds, returning the new scroll and velocity. The list
+// stops dead at either end.
+pub fn coast(scroll: f32, velocity: f32, seconds: f32, max_scroll: f32) -> (f32, f32) {
+    let next_scroll = scroll + velocity * seconds;
+    let next_velocity = velocity * (-DECAY_PER_SECOND * seconds).exp();
+    if next_scroll <= 0.0 {
+        (0.0, 0.0)
+    } else if next_scroll >= max_scroll {
+        (max_scroll, 0.0)
+    } else if next_velocity.abs() < STOP_SPEED {
+        (next_scroll, 0.0)
+    } else {
+        (next_scroll, next_velocity)
+    }
+}
+
+fn seconds_between(earlier: Instant, later: Instant) -> f32 {
+    let duration = later.duration_since(earlier);
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 * 1e-9
+}
+
+struct Grab {
+    start_y: f32,
+    start_scroll: f32,
+    dragging: bool,
+    last_y: f32,
+    last_instant: Instant,
+}
+
+pub struct ScrollWailing<Out> {
+    viewport: Frame,
+    offset: Offset,
+    item_count: usize,
+    item_height: f32,
+    make_item: Rc<Fn(usize) -> Wail2<Out>>,
+    items: BTreeMap<usize, Box<Wailing<Out>>>,
+    scroll: f32,
+    velocity: f32,
+    grab: Option<Grab>,
+    lock: HandLock,
+    last_tick: Option<Instant>,
+    mist_id: u64,
+    beat_id: u64,
+}
+
+impl<Out> ScrollWailing<Out> where Out: Clone + 'static {
+    fn new(viewport: Frame, item_count: usize, item_height: f32, make_item: Rc<Fn(usize) -> Wail2<Out>>) -> Self {
+        let mut scroll_wailing = ScrollWailing {
+            viewport: viewport,
+            offset: Offset::default(),
+            item_count: item_count,
+            item_height: item_height,
+            make_item: make_item,
+            items: BTreeMap::new(),
+            scroll: 0.0,
+            velocity: 0.0,
+            grab: None,
+            lock: HandLock::default(),
+            last_tick: None,
+            mist_id: rand::random::<u64>(),
+            beat_id: rand::random::<u64>(),
+        };
+        scroll_wailing.refresh_items();
+        scroll_wailing
+    }
+
+    fn cage(&self) -> Cage {
+        Cage::from((self.viewport, self.offset))
+    }
+
+    fn set_scroll(&mut self, scroll: f32) {
+        self.scroll = scroll.max(0.0).min(max_scroll(self.viewport.h, self.item_height, self.item_count));
+    }
+
+    fn is_dragging(&self) -> bool {
+        self.grab.as_ref().map(|grab| grab.dragging).unwrap_or(false)
+    }
+
+    // Summons items that scrolled into view, drops those that left, and moves the rest to
+    // their slots. Each item hangs from the top of its slot.
+    fn refresh_items(&mut self) {
+        let (first, end) = visible_range(self.scroll, self.viewport.h, self.item_height, self.item_count);
+        let stale: Vec<usize> = self.items.keys().cloned().filter(|&index| index < first || index >= end).collect();
+        for index in stale {
+            self.items.remove(&index);
+        }
+        let (left, _, _, top, _, near) = self.cage().limits();
+        for index in first..end {
+            let make_item = self.make_item.clone();
+            let item = self.items.entry(index).or_insert_with(|| (*make_item)(index).summon());
+            let size = item.size();
+            let slot_top = top + self.scroll - index as f32 * self.item_height;
+            let target = Cage::from((left, left + size.w, slot_top - size.h, slot_top, near - size.d, near));
+            item.update(&WailingIn::Offset(offset_to_fill(size, &target)));
+        }
+    }
+
+    fn update_hand(&mut self, hand: Hand) {
+        let cage = self.cage();
+        let y = hand.offset.y;
+        let state = button_state(&cage, &hand, default_press_depth(&self.viewport));
+        self.lock = self.lock.follow(&hand, state != ButtonState::Idle);
+        if state == ButtonState::Pressed {
+            let now = Instant::now();
+            match self.grab.take() {
+                None => {
+                    self.velocity = 0.0;
+                    self.grab = Some(Grab { start_y: y, start_scroll: self.scroll, dragging: false, last_y: y, last_instant: now });
+                },
+                Some(mut grab) => {
+                    if !grab.dragging && (y - grab.start_y).abs() > DRAG_SLOP {
+                        grab.dragging = true;
+                    }
+                    if grab.dragging {
+                        let seconds = seconds_between(grab.last_instant, now);
+                        if seconds > 0.0 {
+                            self.velocity = (y - grab.last_y) / seconds;
+                        }
+                        self.set_scroll(grab.start_scroll + y - grab.start_y);
+                    }
+                    grab.last_y = y;
+                    grab.last_instant = now;
+                    self.grab = Some(grab);
+                },
+            }
+        } else if let Some(grab) = self.grab.take() {
+            if !grab.dragging {
+                self.velocity = 0.0;
+            }
+            self.last_tick = Some(Instant::now());
+        }
+    }
+
+    // Hands outside the viewport, or dragging the list, reach the items as a hand well in
+    // front of it so pressed items settle back to idle. Other hands do not reach them while
+    // one is in the viewport.
+    fn item_hand(&self, hand: Hand) -> Hand {
+        let cage = self.cage();
+        let (x, y, z) = hand.offset.tuple();
+        if self.is_dragging() || !cage.contains(x, y, z) {
+            let (left, right, bottom, top, _, near) = cage.limits();
+            Hand::at(Offset::from(((left + right) / 2.0, (bottom + top) / 2.0, near + self.viewport.d))).with_index(hand.index)
+        } else {
+            hand
+        }
+    }
+
+    fn update_items(&mut self, message: &WailingIn) -> Vec<(usize, Out)> {
+        self.items.iter_mut().map(|(&index, item)| (index, item.update(message))).collect()
+    }
+}
+
+impl<Out> Wailing<Vec<(usize, Out)>> for ScrollWailing<Out> where Out: Clone + 'static {
+    fn size(&self) -> Frame {
+        self.viewport
+    }
+
+    // Items are clipped to the viewport. Their mists pass on gaze and focus but not hands,
+    // which come in through the viewport's mist instead.
+    fn view(&self) -> Vision<WailingIn> {
+        let cage = self.cage();
+        let mut vision = Vision::new();
+        add_hand_mist(&mut vision, self.mist_id, cage);
+        for (_, item) in &self.items {
+            vision.add_clipped_vision(item.view(), |wailing_in| match wailing_in {
+                WailingIn::Hand(_) => None,
+                _ => Some(wailing_in),
+            }, &cage);
+        }
+        if self.velocity != 0.0 && self.grab.is_none() {
+            add_tick_beat(&mut vision, self.beat_id, Instant::now() + Duration::from_millis(BEAT_MILLIS));
+        }
+        vision
+    }
+
+    fn update(&mut self, message: &WailingIn) -> Vec<(usize, Out)> {
+        match message {
+            &WailingIn::Offset(offset) => {
+                self.offset = offset;
+                self.refresh_items();
+                Vec::new()
+            },
+            &WailingIn::Hand(hand) => {
+                if !self.lock.admits(&hand) {
+                    return Vec::new();
+                }
+                self.update_hand(hand);
+                self.refresh_items();
+                let item_hand = self.item_hand(hand);
+                self.update_items(&WailingIn::Hand(item_hand))
+            },
+            &WailingIn::Tick => {
+                if self.grab.is_none() && self.velocity != 0.0 {
+                    let now = Instant::now();
+                    // Ticks come as slowly as the animation frames, so coasting covers the whole
+                    // time since the last one rather than a fixed step.
+                    let seconds = self.last_tick.map(|last| seconds_between(last, now)).unwrap_or(0.0);
+                    let limit = max_scroll(self.viewport.h, self.item_height, self.item_count);
+                    let (scroll, velocity) = coast(self.scroll, self.velocity, seconds, limit);
+                    self.scroll = scroll;
+                    self.velocity = velocity;
+                    self.last_tick = if velocity != 0.0 { Some(now) } else { None };
+                    self.refresh_items();
+                }
+                self.update_items(message)
+            },
+            &WailingIn::Select(_) | &WailingIn::FocusWish(..) => self.update_items(message),
+        }
+    }
+
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        self.items.values_mut().flat_map(|item| item.take_wishes()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cage::{Cage, Frame, Offset};
+    use hand::Hand;
+    use wail::core::{color_wail, WailingIn};
+    use color::RED;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn hand_at(y: f32, z: f32) -> WailingIn {
+        WailingIn::Hand(Hand::at(Offset::from((0.0, y, z))))
+    }
+
+    fn counted_list(summons: Rc<Cell<usize>>) -> ScrollWailing<()> {
+        let make_item: Rc<Fn(usize) -> Wail2<()>> = Rc::new(move |_| {
+            summons.set(summons.get() + 1);
+            color_wail(RED, Frame::from((1.0, 0.1, 0.1)))
+        });
+        ScrollWailing::new(Frame::from((1.0, 0.3, 0.1)), 1000, 0.1, make_item)
+    }
+
+    #[test]
+    fn visible_range_covers_partly_shown_items() {
+        assert_eq!((0, 3), visible_range(0.0, 0.3, 0.1, 10));
+        assert_eq!((1, 5), visible_range(0.15, 0.3, 0.1, 10));
+        assert_eq!((8, 10), visible_range(0.85, 0.3, 0.1, 10));
+        assert_eq!(0.7, max_scroll(0.3, 0.1, 10));
+    }
+
+    #[test]
+    fn coasting_slows_and_stops_at_ends() {
+        let (scroll, velocity) = coast(0.5, 1.0, 0.1, 2.0);
+        assert!((scroll - 0.6).abs() < 1e-6);
+        assert!(velocity > 0.0 && velocity < 1.0);
+        assert_eq!((2.0, 0.0), coast(1.95, 1.0, 0.1, 2.0));
+        assert_eq!((0.0, 0.0), coast(0.05, -1.0, 0.1, 2.0));
+        assert_eq!(0.0, coast(0.5, 0.01, 0.01, 2.0).1);
+    }
+
+    #[test]
+    fn only_visible_items_are_summoned() {
+        let summons = Rc::new(Cell::new(0));
+        let list = counted_list(summons.clone());
+        assert_eq!(3, summons.get());
+        assert_eq!(3, list.view().patches.len());
+    }
+
+    #[test]
+    fn dragging_up_scrolls_and_culls() {
+        let summons = Rc::new(Cell::new(0));
+        let mut list = counted_list(summons.clone());
+        list.update(&hand_at(-0.1, -0.02));
+        list.update(&hand_at(0.05, -0.02));
+        assert!((list.scroll - 0.15).abs() < 1e-6);
+        assert_eq!(vec![1, 2, 3, 4], list.items.keys().cloned().collect::<Vec<usize>>());
+        let view = list.view();
+        assert_eq!(4, view.patches.len());
+        assert!(view.patches.values().all(|patch| patch.position.top <= 0.15 + 1e-6));
+        list.update(&hand_at(0.05, 0.2));
+        assert!(list.grab.is_none());
+    }
+
+    #[test]
+    fn coasting_covers_the_time_between_slow_ticks() {
+        let summons = Rc::new(Cell::new(0));
+        let mut list = counted_list(summons.clone());
+        list.velocity = 1.0;
+        list.last_tick = Some(Instant::now() - Duration::from_millis(300));
+        list.update(&WailingIn::Tick);
+        assert!(list.scroll > 0.25 && list.scroll < 0.35);
+    }
+
+    #[test]
+    fn other_hands_do_not_reset_a_drag() {
+        let summons = Rc::new(Cell::new(0));
+        let mut list = counted_list(summons.clone());
+        let other = WailingIn::Hand(Hand::at(Offset::from((2.0, 0.0, 1.0))).with_index(1));
+        list.update(&hand_at(-0.1, -0.02));
+        list.update(&other);
+        list.update(&hand_at(0.0, -0.02));
+        list.update(&other);
+        list.update(&hand_at(0.05, -0.02));
+        assert!((list.scroll - 0.15).abs() < 1e-6);
+        assert!(list.is_dragging());
+    }
+
+    #[test]
+    fn gazing_at_an_item_button_clicks_it() {
+        use wail::button::{button_wail, ButtonMsg};
+
+        let make_item: Rc<Fn(usize) -> Wail2<ButtonMsg>> = Rc::new(|_| button_wail("OK", Frame::from((1.0, 0.1, 0.1))));
+        let mut list = ScrollWailing::new(Frame::from((1.0, 0.3, 0.1)), 10, 0.1, make_item);
+        let vision = list.view();
+        let item_mist_id = *vision.mists.keys().find(|&&id| id != list.mist_id).unwrap();
+        let select = vision.get_message_option(item_mist_id, Wish::GazeSelect).unwrap();
+        let clicked: Vec<usize> = list.update(&select).into_iter()
+                                      .filter(|&(_, ref msg)| *msg == ButtonMsg::Clicked)
+                                      .map(|(index, _)| index)
+                                      .collect();
+        assert_eq!(1, clicked.len());
+        let hand = vision.get_message_option(item_mist_id, Wish::SenseHand(Hand::default()));
+        assert!(hand.is_none());
+    }
+}
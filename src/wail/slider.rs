@@ -0,0 +1,290 @@
+extern crate cage;
+extern crate rand;
+
+use wail::core::{Wail2, Wailing, WailingIn, add_hand_mist};
+use wail::button::{button_state, default_press_depth, ButtonState};
+use vision::Vision;
+use hand::{Hand, HandLock};
+use cage::{Frame, Offset, Cage};
+use color::Color;
+use theme::Paint;
+use patch::{Patch, Sigil};
+use std::rc::Rc;
+
+const TRACK_THICKNESS: f32 = 0.2;
+const THUMB_LENGTH: f32 = 0.1;
+const THUMB_LIFT: f32 = 0.001;
+const ENGAGED_LIGHTEN: f32 = 0.15;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueMsg {
+    None,
+    ValueChanged(f32),
+}
+
+// Values run from min to max. A step of zero leaves them continuous.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ValueRange {
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+impl ValueRange {
+    pub fn new(min: f32, max: f32) -> Self {
+        ValueRange { min: min, max: max, step: 0.0 }
+    }
+
+    pub fn with_step(&self, step: f32) -> Self {
+        ValueRange { step: step, ..*self }
+    }
+
+    pub fn clamp(&self, value: f32) -> f32 {
+        let snapped = if self.step > 0.0 {
+            self.min + ((value - self.min) / self.step).round() * self.step
+        } else {
+            value
+        };
+        snapped.max(self.min).min(self.max)
+    }
+
+    pub fn value_at(&self, fraction: f32) -> f32 {
+        self.clamp(self.min + (self.max - self.min) * fraction.max(0.0).min(1.0))
+    }
+
+    // One step up, or a tenth of the range when continuous, wrapping to min from max.
+    pub fn stepped(&self, value: f32) -> f32 {
+        let step = if self.step > 0.0 { self.step } else { (self.max - self.min) / 10.0 };
+        if value >= self.max { self.min } else { self.clamp(value + step) }
+    }
+
+    pub fn fraction_of(&self, value: f32) -> f32 {
+        if self.max == self.min {
+            0.0
+        } else {
+            ((value - self.min) / (self.max - self.min)).max(0.0).min(1.0)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SliderAxis {
+    Horizontal,
+    Vertical,
+}
+
+pub fn slider_wail(frame: Frame, axis: SliderAxis, range: ValueRange, value: f32) -> Wail2<ValueMsg> {
+    Wail2::create(Rc::new(move || {
+        Box::new(SliderWailing::new(frame, axis, range, value)) as Box<Wailing<ValueMsg>>
+    }))
+}
+
+// Fraction of the way along the slider's axis, from left or bottom, where the point falls.
+pub fn slider_fraction(cage: &Cage, axis: SliderAxis, x: f32, y: f32) -> f32 {
+    let (left, right, bottom, top, _, _) = cage.limits();
+    let (position, start, end) = match axis {
+        SliderAxis::Horizontal => (x, left, right),
+        SliderAxis::Vertical => (y, bottom, top),
+    };
+    if end == start { 0.0 } else { ((position - start) / (end - start)).max(0.0).min(1.0) }
+}
+
+// Engaged values follow the hand. Thumb and track are drawn from the current value, so a
+// parent that never feeds hands still sees the initial value.
+pub struct SliderWailing {
+    frame: Frame,
+    offset: Offset,
+    axis: SliderAxis,
+    range: ValueRange,
+    value: f32,
+    engaged: bool,
+    lock: HandLock,
+    mist_id: u64,
+    track_id: u64,
+    thumb_id: u64,
+}
+
+impl SliderWailing {
+    fn new(frame: Frame, axis: SliderAxis, range: ValueRange, value: f32) -> Self {
+        SliderWailing {
+            frame: frame,
+            offset: Offset::default(),
+            axis: axis,
+            range: range,
+            value: range.clamp(value),
+            engaged: false,
+            lock: HandLock::default(),
+            mist_id: rand::random::<u64>(),
+            track_id: rand::random::<u64>(),
+            thumb_id: rand::random::<u64>(),
+        }
+    }
+
+    fn cage(&self) -> Cage {
+        Cage::from((self.frame, self.offset))
+    }
+
+    fn track_cage(&self) -> Cage {
+        let (left, right, bottom, top, far, near) = self.cage().limits();
+        let (center_x, center_y) = ((left + right) / 2.0, (bottom + top) / 2.0);
+        match self.axis {
+            SliderAxis::Horizontal => {
+                let half = (top - bottom) * TRACK_THICKNESS / 2.0;
+                Cage::from((left, right, center_y - half, center_y + half, far, near))
+            },
+            SliderAxis::Vertical => {
+                let half = (right - left) * TRACK_THICKNESS / 2.0;
+                Cage::from((center_x - half, center_x + half, bottom, top, far, near))
+            },
+        }
+    }
+
+    fn thumb_cage(&self) -> Cage {
+        let (left, right, bottom, top, far, near) = self.cage().limits();
+        let fraction = self.range.fraction_of(self.value);
+        let lift = THUMB_LIFT * 2.0;
+        match self.axis {
+            SliderAxis::Horizontal => {
+                let half = (right - left) * THUMB_LENGTH / 2.0;
+                let center = left + half + (right - left - 2.0 * half) * fraction;
+                Cage::from((center - half, center + half, bottom, top, far + lift, near + lift))
+            },
+            SliderAxis::Vertical => {
+                let half = (top - bottom) * THUMB_LENGTH / 2.0;
+                let center = bottom + half + (top - bottom - 2.0 * half) * fraction;
+                Cage::from((left, right, center - half, center + half, far + lift, near + lift))
+            },
+        }
+    }
+}
+
+pub fn thumb_color(engaged: bool) -> [f32; 4] {
+    if engaged {
+        Color::from(Paint::Accent.resolve()).lighten(ENGAGED_LIGHTEN).to_array()
+    } else {
+        Paint::Accent.resolve()
+    }
+}
+
+// Moves a control to a new value, reporting it only when it actually changed.
+pub fn change_value(current: &mut f32, next: f32) -> ValueMsg {
+    if next != *current {
+        *current = next;
+        ValueMsg::ValueChanged(next)
+    } else {
+        ValueMsg::None
+    }
+}
+
+// Shared by sliders and dials: follows the hand that engaged the control and returns it while
+// it presses. Other hands, and a hand only hovering, give None.
+pub fn engage(lock: &mut HandLock, engaged: &mut bool, hand: &Hand, cage: &Cage, press_depth: f32) -> Option<Hand> {
+    if !lock.admits(hand) {
+        return None;
+    }
+    let state = button_state(cage, hand, press_depth);
+    *lock = lock.follow(hand, state != ButtonState::Idle);
+    *engaged = state == ButtonState::Pressed;
+    if *engaged { Some(*hand) } else { None }
+}
+
+impl Wailing<ValueMsg> for SliderWailing {
+    fn size(&self) -> Frame {
+        self.frame
+    }
+
+    fn view(&self) -> Vision<WailingIn> {
+        let mut vision = Vision::new();
+        add_hand_mist(&mut vision, self.mist_id, self.cage());
+        vision.add_patch(Patch::from_cage(&self.track_cage(), Paint::Surface.resolve(), Sigil::Fill, self.track_id));
+        vision.add_patch(Patch::from_cage(&self.thumb_cage(), thumb_color(self.engaged), Sigil::Fill, self.thumb_id));
+        vision
+    }
+
+    fn update(&mut self, message: &WailingIn) -> ValueMsg {
+        match message {
+            &WailingIn::Offset(offset) => {
+                self.offset = offset;
+                ValueMsg::None
+            },
+            &WailingIn::Hand(hand) => {
+                let cage = self.cage();
+                match engage(&mut self.lock, &mut self.engaged, &hand, &cage, default_press_depth(&self.frame)) {
+                    Some(hand) => {
+                        let fraction = slider_fraction(&cage, self.axis, hand.offset.x, hand.offset.y);
+                        let next_value = self.range.value_at(fraction);
+                        change_value(&mut self.value, next_value)
+                    },
+                    None => ValueMsg::None,
+                }
+            },
+            &WailingIn::Select(mist_id) if mist_id == self.mist_id => {
+                let next_value = self.range.stepped(self.value);
+                change_value(&mut self.value, next_value)
+            },
+            &WailingIn::Tick | &WailingIn::Select(_) | &WailingIn::FocusWish(..) => ValueMsg::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cage::{Cage, Frame, Offset};
+    use hand::{Hand, HandLock};
+    use wail::core::WailingIn;
+
+    #[test]
+    fn range_snaps_and_clamps() {
+        let range = ValueRange::new(0.0, 10.0).with_step(2.5);
+        assert_eq!(5.0, range.clamp(6.0));
+        assert_eq!(7.5, range.clamp(6.5));
+        assert_eq!(10.0, range.clamp(42.0));
+        assert_eq!(0.0, range.value_at(-1.0));
+        assert_eq!(0.25, range.fraction_of(2.5));
+    }
+
+    #[test]
+    fn hand_position_maps_along_axis() {
+        let cage = Cage::from((-0.5, 0.5, 0.0, 0.2, -0.05, 0.05));
+        assert_eq!(0.25, slider_fraction(&cage, SliderAxis::Horizontal, -0.25, 0.1));
+        assert_eq!(1.0, slider_fraction(&cage, SliderAxis::Horizontal, 0.7, 0.1));
+        assert_eq!(0.5, slider_fraction(&cage, SliderAxis::Vertical, 0.0, 0.1));
+    }
+
+    #[test]
+    fn only_engaged_hand_changes_value() {
+        let range = ValueRange::new(0.0, 100.0).with_step(1.0);
+        let mut slider = SliderWailing::new(Frame::from((1.0, 0.2, 0.1)), SliderAxis::Horizontal, range, 50.0);
+        let hovering = Hand::at(Offset::from((0.25, 0.0, 0.04)));
+        let pressing = Hand::at(Offset::from((0.25, 0.0, -0.01)));
+        assert_eq!(ValueMsg::None, slider.update(&WailingIn::Hand(hovering)));
+        assert_eq!(ValueMsg::ValueChanged(75.0), slider.update(&WailingIn::Hand(pressing)));
+        assert_eq!(ValueMsg::None, slider.update(&WailingIn::Hand(pressing)));
+    }
+
+    #[test]
+    fn engage_keeps_to_the_pressing_hand() {
+        let cage = Cage::from((-0.5, 0.5, -0.1, 0.1, -0.05, 0.05));
+        let (mut lock, mut engaged) = (HandLock::default(), false);
+        let pressing = Hand::at(Offset::from((0.2, 0.0, -0.01)));
+        let other = pressing.with_index(1);
+        assert!(engage(&mut lock, &mut engaged, &pressing, &cage, 0.05).is_some());
+        assert!(engage(&mut lock, &mut engaged, &other, &cage, 0.05).is_none());
+        assert!(engaged);
+        let hovering = Hand::at(Offset::from((0.2, 0.0, 0.2)));
+        assert!(engage(&mut lock, &mut engaged, &hovering, &cage, 0.05).is_none());
+        assert!(!engaged && !lock.is_held());
+    }
+
+    #[test]
+    fn selecting_steps_and_wraps() {
+        let range = ValueRange::new(0.0, 1.0).with_step(0.5);
+        let mut slider = SliderWailing::new(Frame::from((1.0, 0.2, 0.1)), SliderAxis::Horizontal, range, 0.0);
+        let select = WailingIn::Select(slider.mist_id);
+        assert_eq!(ValueMsg::ValueChanged(0.5), slider.update(&select));
+        assert_eq!(ValueMsg::ValueChanged(1.0), slider.update(&select));
+        assert_eq!(ValueMsg::ValueChanged(0.0), slider.update(&select));
+        assert_eq!(0.1, ValueRange::new(0.0, 1.0).stepped(0.0));
+    }
+}
@@ -0,0 +1,207 @@
+extern crate cage;
+extern crate rand;
+
+use wail::core::{Wail2, Wailing, WailingIn, add_hand_mist};
+use common::Wish;
+use vision::Vision;
+use hand::Hand;
+use cage::{Frame, Offset, Cage};
+use std::collections::BTreeMap;
+use std::f32::consts::PI;
+use std::rc::Rc;
+
+// Change since the last report: scale multiplies the distance between the hands and rotation
+// turns the line between them counter-clockwise in x and y, in radians.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StretchMsg {
+    None,
+    Changed { scale: f32, rotation: f32 },
+    Ended,
+}
+
+// Tracks two hands grabbing the same cage. As with a single grab, a hand must close its grab
+// inside the cage to take hold. A Stretch is a plain value so stars can keep it in their model.
+#[derive(Clone, Debug, Default)]
+pub struct Stretch {
+    grabbing: BTreeMap<usize, bool>,
+    holding: BTreeMap<usize, Offset>,
+    span: Option<(f32, f32)>,
+}
+
+fn span(a: &Offset, b: &Offset) -> (f32, f32) {
+    let (dx, dy, dz) = (b.x - a.x, b.y - a.y, b.z - a.z);
+    ((dx * dx + dy * dy + dz * dz).sqrt(), dy.atan2(dx))
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    if angle > PI {
+        angle - 2.0 * PI
+    } else if angle < -PI {
+        angle + 2.0 * PI
+    } else {
+        angle
+    }
+}
+
+impl Stretch {
+    pub fn is_stretching(&self) -> bool {
+        self.span.is_some()
+    }
+
+    pub fn sense(&self, cage: &Cage, hand: &Hand) -> (Stretch, StretchMsg) {
+        let mut next = self.clone();
+        let was_grabbing = self.grabbing.get(&hand.index).cloned().unwrap_or(false);
+        let (x, y, z) = hand.offset.tuple();
+        if !hand.grab {
+            next.holding.remove(&hand.index);
+        } else if next.holding.contains_key(&hand.index) || (!was_grabbing && cage.contains(x, y, z)) {
+            next.holding.insert(hand.index, hand.offset);
+        }
+        next.grabbing.insert(hand.index, hand.grab);
+
+        let pair: Vec<Offset> = next.holding.values().take(2).cloned().collect();
+        let msg = if pair.len() == 2 {
+            let (distance, angle) = span(&pair[0], &pair[1]);
+            next.span = Some((distance, angle));
+            match self.span {
+                Some((last_distance, last_angle)) => {
+                    let scale = if last_distance > 0.0 { distance / last_distance } else { 1.0 };
+                    StretchMsg::Changed { scale: scale, rotation: wrap_angle(angle - last_angle) }
+                },
+                None => StretchMsg::None,
+            }
+        } else {
+            next.span = None;
+            if self.span.is_some() { StretchMsg::Ended } else { StretchMsg::None }
+        };
+        (next, msg)
+    }
+}
+
+impl<Out> Wail2<Out> where Out: Clone + 'static {
+    pub fn add_stretch(&self) -> Wail2<StretchMsg> {
+        self.add_stretch_with(|stretch_msg, _| stretch_msg)
+    }
+    pub fn add_stretch_with<R, F>(&self, f: F) -> Wail2<R> where R: Clone + 'static, F: Fn(StretchMsg, Out) -> R + 'static {
+        let base_wail = (*self).clone();
+        let stretch_wail = Wail2::create(Rc::new(move || {
+            Box::new(StretchWailing {
+                offset: Offset::default(),
+                mist_id: rand::random::<u64>(),
+                stretch: Stretch::default(),
+                base_wailing: base_wail.summon(),
+            }) as Box<Wailing<(StretchMsg, Out)>>
+        }));
+        stretch_wail.map(move |(stretch_msg, out)| f(stretch_msg, out))
+    }
+}
+
+// Reports the gesture and leaves resizing to the owner, which knows what the base should become.
+pub struct StretchWailing<BaseOut> {
+    offset: Offset,
+    mist_id: u64,
+    stretch: Stretch,
+    base_wailing: Box<Wailing<BaseOut>>,
+}
+
+impl<BaseOut> Wailing<(StretchMsg, BaseOut)> for StretchWailing<BaseOut> {
+    fn size(&self) -> Frame {
+        (*self.base_wailing).size()
+    }
+
+    fn view(&self) -> Vision<WailingIn> {
+        let mut vision = Vision::new();
+        add_hand_mist(&mut vision, self.mist_id, Cage::from((self.size(), self.offset)));
+        let base_vision = (*self.base_wailing).view();
+        vision.add_vision(base_vision, |x| Some(x));
+        vision
+    }
+
+    // The base sees no hands while both hands hold it.
+    fn update(&mut self, message: &WailingIn) -> (StretchMsg, BaseOut) {
+        match message {
+            &WailingIn::Offset(offset) => {
+                self.offset = offset;
+                (StretchMsg::None, (*self.base_wailing).update(message))
+            },
+            &WailingIn::Hand(hand) => {
+                let (stretch, stretch_msg) = self.stretch.sense(&Cage::from((self.size(), self.offset)), &hand);
+                self.stretch = stretch;
+                let base_out = if self.stretch.is_stretching() {
+                    (*self.base_wailing).update(&WailingIn::Offset(self.offset))
+                } else {
+                    (*self.base_wailing).update(message)
+                };
+                (stretch_msg, base_out)
+            },
+            &WailingIn::Tick | &WailingIn::Select(_) | &WailingIn::FocusWish(..) => (StretchMsg::None, (*self.base_wailing).update(message)),
+        }
+    }
+
+    fn take_wishes(&mut self) -> Vec<Wish> {
+        (*self.base_wailing).take_wishes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wail::core::{color_wail, WailingIn};
+    use hand::Hand;
+    use cage::{Cage, Frame, Offset};
+    use color::RED;
+    use std::f32::consts::PI;
+
+    fn hand(index: usize, x: f32, y: f32, grab: bool) -> Hand {
+        Hand::at(Offset::from((x, y, 0.0))).with_index(index).with_grab(grab)
+    }
+
+    fn assert_changed(expected_scale: f32, expected_rotation: f32, msg: StretchMsg) {
+        match msg {
+            StretchMsg::Changed { scale, rotation } => {
+                assert!((scale - expected_scale).abs() < 1e-5);
+                assert!((rotation - expected_rotation).abs() < 1e-5);
+            },
+            msg => panic!("unexpected {:?}", msg),
+        }
+    }
+
+    fn sense_all(hands: Vec<Hand>) -> Vec<StretchMsg> {
+        let cage = Cage::from((-0.5, 0.5, -0.5, 0.5, -0.1, 0.1));
+        let mut stretch = Stretch::default();
+        let mut msgs = Vec::new();
+        for hand in hands {
+            let (next, msg) = stretch.sense(&cage, &hand);
+            stretch = next;
+            msgs.push(msg);
+        }
+        msgs
+    }
+
+    #[test]
+    fn two_held_hands_report_scale_and_rotation() {
+        let msgs = sense_all(vec![hand(0, -0.2, 0.0, true), hand(1, 0.2, 0.0, true), hand(1, 0.6, 0.0, true),
+                                  hand(1, -0.2, 0.8, true), hand(0, -0.2, 0.0, false)]);
+        assert_eq!(StretchMsg::None, msgs[0]);
+        assert_eq!(StretchMsg::None, msgs[1]);
+        assert_changed(2.0, 0.0, msgs[2]);
+        assert_changed(1.0, PI / 2.0, msgs[3]);
+        assert_eq!(StretchMsg::Ended, msgs[4]);
+    }
+
+    #[test]
+    fn hands_already_grabbing_do_not_take_hold() {
+        let msgs = sense_all(vec![hand(0, -0.2, 0.0, true), hand(1, 0.9, 0.0, true), hand(1, 0.2, 0.0, true),
+                                  hand(1, 0.4, 0.0, true)]);
+        assert!(msgs.iter().all(|msg| *msg == StretchMsg::None));
+    }
+
+    #[test]
+    fn stretch_wail_reports_through_hands() {
+        let mut wailing = color_wail(RED, Frame::from((1.0, 1.0, 0.2))).add_stretch().summon();
+        wailing.update(&WailingIn::Hand(hand(0, -0.25, 0.0, true)));
+        wailing.update(&WailingIn::Hand(hand(1, 0.25, 0.0, true)));
+        let msg = wailing.update(&WailingIn::Hand(hand(1, 0.25, 0.5, true)));
+        assert_changed(2.0f32.sqrt(), PI / 4.0, msg);
+    }
+}